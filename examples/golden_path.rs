@@ -0,0 +1,67 @@
+//! End-to-end walkthrough: build a database from random labeled vectors, search it,
+//! save it to disk in word-vector text format, reload it and check the searches match.
+//!
+//! Run with `cargo run --example golden_path [kdtree|lsh|hybrid]`.
+
+use mini_vector_store_rs::{BackingStorage, LSHParams, QueryPerformance, Vector, VectorDatabase};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+const DIMENSIONS: usize = 8;
+const COUNT: usize = 500;
+const LABELS: [&str; 3] = ["red", "green", "blue"];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let backing_storage = match std::env::args().nth(1).as_deref() {
+        Some("lsh") => BackingStorage::LSHOnly,
+        Some("hybrid") => BackingStorage::Hybrid,
+        _ => BackingStorage::KDTreeOnly,
+    };
+    let lsh_params = LSHParams { seed: Some(42), ..LSHParams::default() };
+    let mut rng = StdRng::seed_from_u64(7);
+
+    let mut db = VectorDatabase::new(DIMENSIONS, backing_storage, Some(lsh_params.clone()));
+    for i in 0..COUNT {
+        let components: Vec<f32> = (0..DIMENSIONS).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        let label = LABELS[i % LABELS.len()];
+        db.insert_with_metadata(Vector::from_vec(components), format!("item{i}"), label.to_string())?;
+    }
+    println!("Built a {backing_storage:?} database with {} vectors using {} distance", db.size(), db.distance_metric());
+
+    let queries: Vec<Vector> = (0..5)
+        .map(|_| Vector::from_vec((0..DIMENSIONS).map(|_| rng.gen_range(-1.0..1.0)).collect()))
+        .collect();
+    let mut before = Vec::with_capacity(queries.len());
+    for query in &queries {
+        let results = db.similarity_search_with_metadata(query, 3, QueryPerformance::Accurate)?;
+        for result in &results {
+            println!("  {} ({}) at {:.4}", result.key, result.metadata, result.distance);
+        }
+        before.push(db.similarity_search(query, 3, QueryPerformance::Accurate)?);
+    }
+
+    // Rust prints f32 with the shortest representation that parses back to the same value
+    let path = std::env::temp_dir().join("mini_vector_store_golden_path.txt");
+    let (keys, data, dimensions) = db.to_flat();
+    let mut writer = BufWriter::new(File::create(&path)?);
+    writeln!(writer, "{} {}", keys.len(), dimensions)?;
+    for (key, components) in keys.iter().zip(data.chunks(dimensions)) {
+        let values: Vec<String> = components.iter().map(f32::to_string).collect();
+        writeln!(writer, "{key} {}", values.join(" "))?;
+    }
+    writer.flush()?;
+    println!("Saved {} vectors to {}", keys.len(), path.display());
+
+    let (reloaded, words) = VectorDatabase::from_word_vectors(BufReader::new(File::open(&path)?), backing_storage, Some(lsh_params))?;
+    assert_eq!(words, keys);
+    assert_eq!(reloaded.content_hash(), db.content_hash());
+    for (query, expected) in queries.iter().zip(&before) {
+        assert_eq!(&reloaded.similarity_search(query, 3, QueryPerformance::Accurate)?, expected);
+    }
+    println!("Reloaded database returns identical results for {} queries", queries.len());
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
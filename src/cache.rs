@@ -0,0 +1,185 @@
+use crate::error::VectorError;
+use crate::{QueryPerformance, Vector, VectorDatabase};
+use std::collections::HashMap;
+
+/// Keyed on the exact bit pattern of the query, so distinct queries never share an entry
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    query_bits: Vec<u32>,
+    k: usize,
+    performance: QueryPerformance,
+}
+
+struct CacheEntry {
+    results: Vec<(String, f32)>,
+    last_used: u64,
+}
+
+/// Vector database wrapper that memoizes similarity search results in an LRU cache
+pub struct CachedVectorDatabase {
+    database: VectorDatabase,
+    capacity: usize,
+    entries: HashMap<CacheKey, CacheEntry>,
+    content_hash: u64,
+    tick: u64,
+}
+
+impl CachedVectorDatabase {
+    /// Wrap a database with a cache holding at most `capacity` query results
+    pub fn new(database: VectorDatabase, capacity: usize) -> Self {
+        let content_hash = database.content_hash();
+        Self {
+            database,
+            capacity,
+            entries: HashMap::new(),
+            content_hash,
+            tick: 0,
+        }
+    }
+
+    /// Perform similarity search, reusing a cached result if the query and database contents are unchanged
+    pub fn similarity_search(&mut self, query: &Vector, k: usize, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        if self.database.content_hash() != self.content_hash {
+            self.entries.clear();
+            self.content_hash = self.database.content_hash();
+        }
+
+        self.tick += 1;
+        let cache_key = CacheKey {
+            query_bits: Self::query_bits(query),
+            k,
+            performance,
+        };
+
+        if let Some(entry) = self.entries.get_mut(&cache_key) {
+            entry.last_used = self.tick;
+            return Ok(entry.results.clone());
+        }
+
+        let results = self.database.similarity_search(query, k, performance)?;
+        if self.capacity > 0 {
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries.insert(cache_key, CacheEntry {
+                results: results.clone(),
+                last_used: self.tick,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Bits of the query components, NaN payloads and the sign of zero included
+    fn query_bits(query: &Vector) -> Vec<u32> {
+        query.data().iter().map(|x| x.to_bits()).collect()
+    }
+
+    /// Drop the entry that was used longest ago
+    fn evict_least_recently_used(&mut self) {
+        if let Some(oldest) = self.entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(cache_key, _)| cache_key.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+    }
+
+    /// Get a reference to the wrapped database
+    pub fn database(&self) -> &VectorDatabase {
+        &self.database
+    }
+
+    /// Get a mutable reference to the wrapped database, changes to its contents invalidate the cache
+    pub fn database_mut(&mut self) -> &mut VectorDatabase {
+        &mut self.database
+    }
+
+    /// Unwrap the database, discarding the cache
+    pub fn into_inner(self) -> VectorDatabase {
+        self.database
+    }
+
+    /// Get the number of cached query results
+    pub fn cached_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Remove all cached query results
+    pub fn clear_cache(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BackingStorage;
+
+    fn cached_database() -> CachedVectorDatabase {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[1.0, 2.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[3.0, 4.0]), "test2".to_string()).unwrap();
+        CachedVectorDatabase::new(db, 2)
+    }
+
+    #[test]
+    fn test_cache_hit_returns_same_result() {
+        let mut cached = cached_database();
+        let query = Vector::from_slice(&[1.0, 1.0]);
+
+        let first = cached.similarity_search(&query, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(cached.cached_count(), 1);
+
+        let second = cached.similarity_search(&query, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(cached.cached_count(), 1);
+        assert_eq!(first, second);
+        assert_eq!(first[0].0, "test1");
+    }
+
+    #[test]
+    fn test_cache_invalidated_on_mutation() {
+        let mut cached = cached_database();
+        let query = Vector::from_slice(&[1.0, 1.0]);
+
+        let before = cached.similarity_search(&query, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(before[0].0, "test1");
+
+        cached.database_mut().insert(Vector::from_slice(&[1.0, 1.0]), "test3".to_string()).unwrap();
+        let after = cached.similarity_search(&query, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(after[0].0, "test3");
+        assert_eq!(cached.cached_count(), 1);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cached = cached_database();
+        let query1 = Vector::from_slice(&[1.0, 1.0]);
+        let query2 = Vector::from_slice(&[2.0, 2.0]);
+        let query3 = Vector::from_slice(&[3.0, 3.0]);
+
+        cached.similarity_search(&query1, 1, QueryPerformance::Accurate).unwrap();
+        cached.similarity_search(&query2, 1, QueryPerformance::Accurate).unwrap();
+        cached.similarity_search(&query1, 1, QueryPerformance::Accurate).unwrap();
+        cached.similarity_search(&query3, 1, QueryPerformance::Accurate).unwrap();
+
+        assert_eq!(cached.cached_count(), 2);
+        let query2_key = CacheKey {
+            query_bits: CachedVectorDatabase::query_bits(&query2),
+            k: 1,
+            performance: QueryPerformance::Accurate,
+        };
+        assert!(!cached.entries.contains_key(&query2_key));
+    }
+
+    #[test]
+    fn test_cache_keys_on_exact_query_bits() {
+        let mut cached = cached_database();
+        let positive_zero = Vector::from_slice(&[0.0, 1.0]);
+        let negative_zero = Vector::from_slice(&[-0.0, 1.0]);
+
+        cached.similarity_search(&positive_zero, 1, QueryPerformance::Accurate).unwrap();
+        cached.similarity_search(&negative_zero, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(cached.cached_count(), 2);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::error::VectorError;
+
+/// Sign bits of a vector packed 64 per word, bit i is set when component i is positive
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BinaryCode {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BinaryCode {
+    /// Pack the sign bits of the given components
+    pub fn from_components(components: &[f32]) -> Self {
+        let mut words = vec![0u64; components.len().div_ceil(64)];
+        for (i, &x) in components.iter().enumerate() {
+            if x > 0.0 {
+                words[i / 64] |= 1 << (i % 64);
+            }
+        }
+        Self { words, len: components.len() }
+    }
+
+    /// Get the number of bits, one per component of the source vector
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the code holds no bits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the packed words, unused high bits of the last word are zero
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Get the bit for component `index`, panics like slice indexing when `index >= len()`
+    pub fn bit(&self, index: usize) -> bool {
+        assert!(index < self.len, "bit index {index} out of range for a code of {} bits", self.len);
+        self.words[index / 64] >> (index % 64) & 1 == 1
+    }
+
+    /// Count the differing bits between two codes of equal length
+    pub fn hamming_distance(&self, other: &BinaryCode) -> Result<u32, VectorError> {
+        if self.len != other.len {
+            return Err(VectorError::DimensionsMismatch { expected: self.len, found: other.len });
+        }
+
+        Ok(self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{Distance, Vector};
+
+    #[test]
+    fn test_binary_code_hamming_matches_unpacked() {
+        let a = Vector::from_vec((0..130).map(|i| ((i * 7) % 11) as f32 - 5.0).collect());
+        let b = Vector::from_vec((0..130).map(|i| ((i * 3) % 13) as f32 - 6.0).collect());
+        let (code_a, code_b) = (a.to_binary_code(), b.to_binary_code());
+        assert_eq!(code_a.len(), 130);
+        assert_eq!(code_a.words().len(), 3);
+
+        let unpacked = a.data()
+            .iter()
+            .zip(b.data())
+            .filter(|(x, y)| (**x > 0.0) != (**y > 0.0))
+            .count() as u32;
+        assert_eq!(code_a.hamming_distance(&code_b).unwrap(), unpacked);
+        assert_eq!(code_a.hamming_distance(&code_a).unwrap(), 0);
+        assert!((0..130).all(|i| code_a.bit(i) == (a[i] > 0.0)));
+
+        let short = Vector::from_slice(&[1.0]).to_binary_code();
+        assert!(code_a.hamming_distance(&short).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "bit index 130 out of range")]
+    fn test_binary_code_bit_out_of_range_panics() {
+        // Index 130 still falls inside the last packed word
+        let code = Vector::from_vec(vec![1.0; 130]).to_binary_code();
+        code.bit(130);
+    }
+
+    #[test]
+    fn test_binary_code_shortlist_then_rerank() {
+        let vectors: Vec<Vector> = (0..40)
+            .map(|i| Vector::from_vec((0..16).map(|j| ((i * 31 + j * 17) % 23) as f32 - 11.0).collect()))
+            .collect();
+        let query = vectors[17].add(&Vector::from_vec(vec![0.1; 16])).unwrap();
+        let query_code = query.to_binary_code();
+
+        let mut shortlist: Vec<(u32, usize)> = vectors
+            .iter()
+            .enumerate()
+            .map(|(index, vector)| (vector.to_binary_code().hamming_distance(&query_code).unwrap(), index))
+            .collect();
+        shortlist.sort();
+        shortlist.truncate(5);
+
+        let best = shortlist
+            .iter()
+            .map(|&(_, index)| (Distance::Euclidean.distance(&query, &vectors[index]).unwrap(), index))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap();
+        assert_eq!(best.1, 17);
+    }
+}
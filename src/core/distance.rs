@@ -1,57 +1,295 @@
 use crate::core::vector::Vector;
 use crate::error::VectorError;
 
+/// Distance metrics, every variant returns a distance where lower means closer and identical vectors score 0
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Distance {
+    /// L2 distance, ranges over [0, inf)
     Euclidean,
+    /// L1 distance, ranges over [0, inf)
     Manhattan,
+    /// Cosine distance `1 - cos(a, b)`, ranges over [0, 2]
     CosineSim,
+    /// Cosine distance with negative similarity clamped to zero, ranges over [0, 1]
+    CosinePositive,
+    /// Sorensen-Dice distance over the sets of nonzero components, ranges over [0, 1]
+    Dice,
 }
 
 impl Distance {
     /// Calculate the distance between two vectors using this metric
     pub fn distance(&self, v1: &Vector, v2: &Vector) -> Result<f32, VectorError> {
-        if v1.size() != v2.size() {
-            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: v2.size() });
+        self.distance_slice(v1.data(), v2.data())
+    }
+
+    /// Calculate the distance between two slices of components using this metric
+    pub fn distance_slice(&self, v1: &[f32], v2: &[f32]) -> Result<f32, VectorError> {
+        if let Some(distance) = self.shortcut_distance(v1, v2)? {
+            return Ok(distance);
+        }
+        let (norm1, norm2) = match self {
+            Distance::CosineSim | Distance::CosinePositive => (slice_norm(v1), slice_norm(v2)),
+            _ => (0.0, 0.0),
+        };
+        self.compute_checked(v1, v2, norm1, norm2)
+    }
+
+    /// Calculate the distance over only the first `prefix_dim` components, as for truncated embeddings
+    pub fn distance_prefix(&self, v1: &Vector, v2: &Vector, prefix_dim: usize) -> Result<f32, VectorError> {
+        let shortest = v1.size().min(v2.size());
+        if prefix_dim > shortest {
+            return Err(VectorError::DimensionsMismatch { expected: prefix_dim, found: shortest });
+        }
+        self.distance_slice(&v1.data()[..prefix_dim], &v2.data()[..prefix_dim])
+    }
+
+    /// Calculate the distance given the L2 norms of both slices, which only the cosine metrics read
+    pub(crate) fn distance_with_norms(&self, v1: &[f32], v2: &[f32], norm1: f32, norm2: f32) -> Result<f32, VectorError> {
+        if let Some(distance) = self.shortcut_distance(v1, v2)? {
+            return Ok(distance);
+        }
+        self.compute_checked(v1, v2, norm1, norm2)
+    }
+
+    /// Check the lengths and answer for empty or identical inputs without computing the metric or any norm.
+    /// The bitwise comparison stops at the first differing component, so distinct inputs cost little.
+    fn shortcut_distance(&self, v1: &[f32], v2: &[f32]) -> Result<Option<f32>, VectorError> {
+        if v1.len() != v2.len() {
+            return Err(VectorError::DimensionsMismatch { expected: v1.len(), found: v2.len() });
         }
         
+        // Two empty vectors are equal, so every metric puts them at distance 0
+        if v1.is_empty() {
+            return Ok(Some(0.0));
+        }
+        
+        if v1.as_ptr() == v2.as_ptr() || v1.iter().zip(v2).all(|(a, b)| a.to_bits() == b.to_bits()) {
+            return Ok(self.self_distance(v1));
+        }
+        Ok(None)
+    }
+
+    /// Distance from a finite vector to itself without the full computation, None when it needs computing:
+    /// non-finite components must still be reported, and cosine metrics put a zero vector at distance 1
+    fn self_distance(&self, v: &[f32]) -> Option<f32> {
+        if !v.iter().all(|x| x.is_finite()) {
+            return None;
+        }
+        match self {
+            Distance::CosineSim | Distance::CosinePositive if v.iter().all(|x| *x == 0.0) => None,
+            _ => Some(0.0),
+        }
+    }
+
+    /// Compute the metric, rejecting an Lp sum that came out non-finite so it cannot corrupt a ranking.
+    /// A non-finite input component is reported as such, only finite inputs count as an overflow;
+    /// `distance_f64` accumulates in f64 and stays finite for any finite f32 input.
+    fn compute_checked(&self, v1: &[f32], v2: &[f32], norm1: f32, norm2: f32) -> Result<f32, VectorError> {
+        let distance = self.compute(v1, v2, norm1, norm2);
+        if self.is_coordinate_separable() && !distance.is_finite() {
+            if let Some(index) = v1.iter().zip(v2).position(|(a, b)| !a.is_finite() || !b.is_finite()) {
+                return Err(VectorError::NonFiniteComponent { index });
+            }
+            return Err(VectorError::NumericalOverflow(*self));
+        }
+        Ok(distance)
+    }
+
+    /// Distance from a stored candidate to a finite query, None when the candidate holds
+    /// non-finite components and cannot be ranked, so index searches skip it
+    pub(crate) fn candidate_distance(&self, candidate: &[f32], query: &[f32]) -> Result<Option<f32>, VectorError> {
+        Self::rankable(self.distance_slice(candidate, query))
+    }
+
+    /// `candidate_distance` given the L2 norms of both slices, as for `distance_with_norms`
+    pub(crate) fn candidate_distance_with_norms(&self, candidate: &[f32], query: &[f32], candidate_norm: f32, query_norm: f32) -> Result<Option<f32>, VectorError> {
+        Self::rankable(self.distance_with_norms(candidate, query, candidate_norm, query_norm))
+    }
+
+    /// Turn a distance that cannot be ranked because of non-finite input into None
+    fn rankable(result: Result<f32, VectorError>) -> Result<Option<f32>, VectorError> {
+        match result {
+            Err(VectorError::NonFiniteComponent { .. }) => Ok(None),
+            Ok(distance) if distance.is_nan() => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
+    /// Metric formulas over slices already checked to have equal, nonzero length
+    fn compute(&self, v1: &[f32], v2: &[f32], norm1: f32, norm2: f32) -> f32 {
         match self {
             Distance::Euclidean => {
-                Ok(v1.data()
-                    .iter()
-                    .zip(v2.data())
+                v1.iter()
+                    .zip(v2)
                     .map(|(a, b)| (a - b).powi(2))
                     .sum::<f32>()
-                    .sqrt())
+                    .sqrt()
             }
             Distance::Manhattan => {
-                Ok(v1.data()
-                    .iter()
-                    .zip(v2.data())
+                v1.iter()
+                    .zip(v2)
                     .map(|(a, b)| (a - b).abs())
-                    .sum())
+                    .sum()
             }
-            Distance::CosineSim => {
-                let dot = v1.dot_product(v2)?;
-                let norm1 = v1.norm();
-                let norm2 = v2.norm();
+            Distance::CosineSim => 1.0 - Self::cosine_similarity(v1, v2, norm1, norm2),
+            Distance::CosinePositive => 1.0 - Self::cosine_similarity(v1, v2, norm1, norm2).max(0.0),
+            Distance::Dice => {
+                let (mut count1, mut count2, mut shared) = (0usize, 0usize, 0usize);
+                for (a, b) in v1.iter().zip(v2) {
+                    count1 += (*a != 0.0) as usize;
+                    count2 += (*b != 0.0) as usize;
+                    shared += (*a != 0.0 && *b != 0.0) as usize;
+                }
 
-                if norm1 == 0.0 || norm2 == 0.0 {
-                    return Ok(1.0);
+                if count1 + count2 == 0 {
+                    return 0.0;
                 }
+                1.0 - (2 * shared) as f32 / (count1 + count2) as f32
+            }
+        }
+    }
+
+    /// Cosine similarity clamped to [-1, 1], zero when either vector has zero norm
+    fn cosine_similarity(v1: &[f32], v2: &[f32], norm1: f32, norm2: f32) -> f32 {
+        if norm1 == 0.0 || norm2 == 0.0 {
+            return 0.0;
+        }
 
-                let cosine_similarity = (dot / (norm1 * norm2)).clamp(-1.0, 1.0);
-                Ok(1.0 - cosine_similarity)
+        let dot: f32 = v1.iter().zip(v2).map(|(a, b)| a * b).sum();
+        (dot / (norm1 * norm2)).clamp(-1.0, 1.0)
+    }
+
+    /// Calculate the distance accumulating in f64 in index order, for rankings that must not depend on
+    /// how the platform rounds intermediate f32 sums
+    pub fn distance_f64(&self, v1: &Vector, v2: &Vector) -> Result<f64, VectorError> {
+        if v1.size() != v2.size() {
+            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: v2.size() });
+        }
+        if v1.size() == 0 {
+            return Ok(0.0);
+        }
+
+        let pairs = v1.data().iter().zip(v2.data()).map(|(a, b)| (*a as f64, *b as f64));
+        Ok(match self {
+            Distance::Euclidean => pairs.map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt(),
+            Distance::Manhattan => pairs.map(|(a, b)| (a - b).abs()).sum(),
+            Distance::CosineSim | Distance::CosinePositive => {
+                let (mut dot, mut norm1, mut norm2) = (0.0f64, 0.0f64, 0.0f64);
+                for (a, b) in pairs {
+                    dot += a * b;
+                    norm1 += a * a;
+                    norm2 += b * b;
+                }
+                let similarity = if norm1 == 0.0 || norm2 == 0.0 {
+                    0.0
+                } else {
+                    (dot / (norm1.sqrt() * norm2.sqrt())).clamp(-1.0, 1.0)
+                };
+                match self {
+                    Distance::CosinePositive => 1.0 - similarity.max(0.0),
+                    _ => 1.0 - similarity,
+                }
             }
+            Distance::Dice => self.compute(v1.data(), v2.data(), 0.0, 0.0) as f64,
+        })
+    }
+
+    /// Map a distance from this metric to a relevance score in [0, 1] where 1 is best:
+    /// Euclidean and Manhattan use `1 / (1 + d)`, CosineSim uses `1 - d / 2`,
+    /// and CosinePositive and Dice, already bounded by 1, use `1 - d`
+    pub fn similarity_score(&self, distance: f32) -> f32 {
+        let score = match self {
+            Distance::Euclidean | Distance::Manhattan => 1.0 / (1.0 + distance),
+            Distance::CosineSim => 1.0 - distance / 2.0,
+            Distance::CosinePositive | Distance::Dice => 1.0 - distance,
+        };
+        score.clamp(0.0, 1.0)
+    }
+
+    /// Gaussian RBF kernel similarity `exp(-gamma * ||a - b||^2)` in (0, 1], summing the squared
+    /// differences directly. The kernel is defined on Euclidean distance, so other metrics are rejected.
+    pub fn rbf_similarity(&self, v1: &Vector, v2: &Vector, gamma: f32) -> Result<f32, VectorError> {
+        if *self != Distance::Euclidean {
+            return Err(VectorError::UnsupportedMetric(*self));
+        }
+        Self::check_gamma(gamma)?;
+        if v1.size() != v2.size() {
+            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: v2.size() });
+        }
+        let squared_distance: f32 = v1.data().iter().zip(v2.data()).map(|(a, b)| (a - b) * (a - b)).sum();
+        Ok((-gamma * squared_distance).exp())
+    }
+
+    /// Map a Euclidean distance, as returned by a search, to the Gaussian kernel value
+    /// `exp(-gamma * distance^2)`, gamma must be positive and finite
+    pub fn rbf_kernel(distance: f32, gamma: f32) -> Result<f32, VectorError> {
+        Self::check_gamma(gamma)?;
+        Ok((-gamma * distance * distance).exp())
+    }
+
+    /// Reject a kernel width that is not positive and finite
+    fn check_gamma(gamma: f32) -> Result<(), VectorError> {
+        if !(gamma > 0.0 && gamma.is_finite()) {
+            return Err(VectorError::InvalidParameter(format!("gamma must be positive and finite, got {gamma}")));
+        }
+        Ok(())
+    }
+
+    /// Whether higher values mean closer, always false because every variant reports a distance;
+    /// a similarity-style metric must be given as a distance, such as the `1 - cos` of CosineSim
+    pub fn is_similarity(&self) -> bool {
+        match self {
+            Distance::Euclidean | Distance::Manhattan | Distance::CosineSim | Distance::CosinePositive | Distance::Dice => false,
         }
     }
 
+    /// Whether the metric is built from per-coordinate terms, so a single axis bounds the full distance
+    pub fn is_coordinate_separable(&self) -> bool {
+        match self {
+            Distance::Euclidean | Distance::Manhattan => true,
+            Distance::CosineSim | Distance::CosinePositive | Distance::Dice => false,
+        }
+    }
+
+    /// Distance contributed by a single coordinate, a lower bound on the full distance.
+    /// Returns None for metrics that are not coordinate-separable.
+    pub fn axis_distance(&self, a: f32, b: f32) -> Option<f32> {
+        match self {
+            Distance::Euclidean | Distance::Manhattan => Some((a - b).abs()),
+            Distance::CosineSim | Distance::CosinePositive | Distance::Dice => None,
+        }
+    }
+
+    /// Suggest a metric for the given data: CosineSim when every vector has norm within 1e-3 of 1,
+    /// Manhattan when every component is a nonnegative whole number as in count data,
+    /// and Euclidean otherwise, including for empty input
+    pub fn recommend(vectors: &[Vector]) -> Distance {
+        const NORM_TOLERANCE: f32 = 1e-3;
+
+        if vectors.is_empty() {
+            return Distance::Euclidean;
+        }
+        if vectors.iter().all(|vector| (vector.norm() - 1.0).abs() <= NORM_TOLERANCE) {
+            return Distance::CosineSim;
+        }
+        let count_like = vectors
+            .iter()
+            .flat_map(|vector| vector.data())
+            .all(|x| *x >= 0.0 && x.fract() == 0.0);
+        if count_like {
+            return Distance::Manhattan;
+        }
+        Distance::Euclidean
+    }
+
     /// Get the name of this distance metric as a string
     pub fn name(&self) -> &'static str {
         match self {
             Distance::Euclidean => "euclidean",
             Distance::Manhattan => "manhattan",
             Distance::CosineSim => "cosinesim",
+            Distance::CosinePositive => "cosinepos",
+            Distance::Dice => "dice",
         }
     }
 
@@ -61,11 +299,91 @@ impl Distance {
             "euclidean" | "e" => Some(Distance::Euclidean),
             "manhattan" | "m" => Some(Distance::Manhattan),
             "cosinesim" | "c" => Some(Distance::CosineSim),
+            "cosinepos" | "cp" => Some(Distance::CosinePositive),
+            "dice" | "sorensen" => Some(Distance::Dice),
             _ => None,
         }
     }
 }
 
+/// L2 norm of a slice, computed the same way as `Vector::norm`
+fn slice_norm(data: &[f32]) -> f32 {
+    data.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+/// A distance between two vectors, lower values are closer
+pub trait Metric {
+    /// Calculate the distance between two vectors
+    fn distance(&self, v1: &Vector, v2: &Vector) -> Result<f32, VectorError>;
+}
+
+impl Metric for Distance {
+    fn distance(&self, v1: &Vector, v2: &Vector) -> Result<f32, VectorError> {
+        Distance::distance(self, v1, v2)
+    }
+}
+
+/// Mahalanobis distance `sqrt((a - b)^T inv_cov (a - b))` for a given inverse covariance matrix
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mahalanobis {
+    pub inv_cov: Vec<Vec<f32>>,
+}
+
+impl Metric for Mahalanobis {
+    fn distance(&self, v1: &Vector, v2: &Vector) -> Result<f32, VectorError> {
+        if v1.size() != v2.size() {
+            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: v2.size() });
+        }
+        if self.inv_cov.len() != v1.size() {
+            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: self.inv_cov.len() });
+        }
+        if let Some(row) = self.inv_cov.iter().find(|row| row.len() != v1.size()) {
+            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: row.len() });
+        }
+
+        let diff = v1.subtract(v2)?;
+        let quadratic_form: f32 = self.inv_cov
+            .iter()
+            .zip(diff.data())
+            .map(|(row, d_i)| {
+                d_i * row
+                    .iter()
+                    .zip(diff.data())
+                    .map(|(m_ij, d_j)| m_ij * d_j)
+                    .sum::<f32>()
+            })
+            .sum();
+
+        // A positive semi-definite matrix can still round slightly below zero
+        Ok(quadratic_form.max(0.0).sqrt())
+    }
+}
+
+/// Weighted sum of several metrics, such as `0.7 * cosine + 0.3 * euclidean`.
+/// Components are not rescaled, so the caller must pick weights that make their ranges comparable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnsembleMetric {
+    pub components: Vec<(Distance, f32)>,
+}
+
+impl Metric for EnsembleMetric {
+    fn distance(&self, v1: &Vector, v2: &Vector) -> Result<f32, VectorError> {
+        if v1.size() != v2.size() {
+            return Err(VectorError::DimensionsMismatch { expected: v1.size(), found: v2.size() });
+        }
+        if v1.size() == 0 {
+            return Ok(0.0);
+        }
+
+        let (norm1, norm2) = (v1.norm(), v2.norm());
+        let mut total = 0.0;
+        for (metric, weight) in &self.components {
+            total += weight * metric.compute_checked(v1.data(), v2.data(), norm1, norm2)?;
+        }
+        Ok(total)
+    }
+}
+
 impl std::fmt::Display for Distance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name())
@@ -118,6 +436,233 @@ mod tests {
         assert!((d.distance(&v1, &v4).unwrap() - 1.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_cosine_positive_distance_calculation() {
+        let d = Distance::CosinePositive;
+        let v1 = Vector::from_slice(&[1.0, 0.0]);
+        assert!((d.distance(&v1, &v1).unwrap() - 0.0).abs() < 1e-6);
+
+        let perpendicular = Vector::from_slice(&[0.0, 1.0]);
+        assert!((d.distance(&v1, &perpendicular).unwrap() - 1.0).abs() < 1e-6);
+
+        // Opposite vectors are clamped to the same distance as perpendicular ones
+        let opposite = Vector::from_slice(&[-1.0, 0.0]);
+        assert!((d.distance(&v1, &opposite).unwrap() - 1.0).abs() < 1e-6);
+
+        let zero = Vector::from_slice(&[0.0, 0.0]);
+        assert_eq!(d.distance(&v1, &zero).unwrap(), 1.0);
+        assert_eq!(Distance::CosineSim.distance(&v1, &zero).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_dice_distance_calculation() {
+        let d = Distance::Dice;
+        let v1 = Vector::from_slice(&[1.0, 0.0, 1.0, 1.0]);
+        assert_eq!(d.distance(&v1, &v1).unwrap(), 0.0);
+
+        let disjoint = Vector::from_slice(&[0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(d.distance(&v1, &disjoint).unwrap(), 1.0);
+
+        // |A| = 3, |B| = 2, |A and B| = 1, so 1 - 2 / 5 = 0.6
+        let partial = Vector::from_slice(&[1.0, 1.0, 0.0, 0.0]);
+        assert!((d.distance(&v1, &partial).unwrap() - 0.6).abs() < 1e-6);
+
+        let zero = Vector::from_slice(&[0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(d.distance(&zero, &zero).unwrap(), 0.0);
+
+        assert!(d.distance(&v1, &Vector::from_slice(&[1.0, 0.0])).is_err());
+    }
+
+    #[test]
+    fn test_distance_empty_vectors() {
+        let empty = Vector::new(0);
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            assert_eq!(d.distance(&empty, &empty).unwrap(), 0.0, "{d}");
+            assert!(d.distance(&empty, &Vector::new(1)).is_err(), "{d}");
+        }
+    }
+
+    #[test]
+    fn test_distance_single_element_vectors() {
+        let a = Vector::from_slice(&[2.0]);
+        let b = Vector::from_slice(&[5.0]);
+        let negative = Vector::from_slice(&[-5.0]);
+        let zero = Vector::from_slice(&[0.0]);
+
+        assert_eq!(Distance::Euclidean.distance(&a, &b).unwrap(), 3.0);
+        assert_eq!(Distance::Euclidean.distance(&a, &negative).unwrap(), 7.0);
+
+        assert_eq!(Distance::Manhattan.distance(&a, &b).unwrap(), 3.0);
+        assert_eq!(Distance::Manhattan.distance(&a, &negative).unwrap(), 7.0);
+
+        assert_eq!(Distance::CosineSim.distance(&a, &b).unwrap(), 0.0);
+        assert_eq!(Distance::CosineSim.distance(&a, &negative).unwrap(), 2.0);
+        assert_eq!(Distance::CosineSim.distance(&a, &zero).unwrap(), 1.0);
+
+        assert_eq!(Distance::CosinePositive.distance(&a, &b).unwrap(), 0.0);
+        assert_eq!(Distance::CosinePositive.distance(&a, &negative).unwrap(), 1.0);
+        assert_eq!(Distance::CosinePositive.distance(&a, &zero).unwrap(), 1.0);
+
+        assert_eq!(Distance::Dice.distance(&a, &b).unwrap(), 0.0);
+        assert_eq!(Distance::Dice.distance(&a, &zero).unwrap(), 1.0);
+        assert_eq!(Distance::Dice.distance(&zero, &zero).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_distance_similarity_score() {
+        assert_eq!(Distance::Euclidean.similarity_score(0.0), 1.0);
+        assert_eq!(Distance::Euclidean.similarity_score(1.0), 0.5);
+        assert_eq!(Distance::Manhattan.similarity_score(3.0), 0.25);
+        assert_eq!(Distance::CosineSim.similarity_score(2.0), 0.0);
+        assert_eq!(Distance::CosineSim.similarity_score(1.0), 0.5);
+        assert_eq!(Distance::CosinePositive.similarity_score(0.25), 0.75);
+        assert_eq!(Distance::Dice.similarity_score(1.0), 0.0);
+    }
+
+    #[test]
+    fn test_distance_rbf_similarity() {
+        let origin = Vector::from_slice(&[0.0, 0.0]);
+        assert_eq!(Distance::Euclidean.rbf_similarity(&origin, &origin, 0.5).unwrap(), 1.0);
+
+        let mut previous = 1.0;
+        for step in 1..10 {
+            let point = Vector::from_slice(&[step as f32 * 0.5, 0.0]);
+            let similarity = Distance::Euclidean.rbf_similarity(&origin, &point, 0.5).unwrap();
+            assert!(similarity < previous && similarity > 0.0);
+            previous = similarity;
+        }
+
+        let point = Vector::from_slice(&[1.0, 1.0]);
+        assert!((Distance::Euclidean.rbf_similarity(&origin, &point, 0.5).unwrap() - (-1.0f32).exp()).abs() < 1e-6);
+        assert!(matches!(Distance::Euclidean.rbf_similarity(&origin, &point, 0.0), Err(VectorError::InvalidParameter(_))));
+        assert!(matches!(Distance::Euclidean.rbf_similarity(&origin, &point, f32::NAN), Err(VectorError::InvalidParameter(_))));
+        assert!(matches!(
+            Distance::Euclidean.rbf_similarity(&origin, &Vector::from_slice(&[1.0]), 1.0),
+            Err(VectorError::DimensionsMismatch { .. })
+        ));
+        for metric in [Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            assert!(matches!(metric.rbf_similarity(&origin, &point, 0.5), Err(VectorError::UnsupportedMetric(_))));
+        }
+    }
+
+    #[test]
+    fn test_distance_identical_vectors_short_circuit() {
+        let v = Vector::from_slice(&[0.1, -0.7, 0.3, 1e-3]);
+        let copy = v.clone();
+        let near = Vector::from_slice(&[0.1, -0.7, 0.3, 1.001e-3]);
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            assert_eq!(d.distance(&v, &v).unwrap(), 0.0, "{d}");
+            assert_eq!(d.distance(&v, &copy).unwrap(), 0.0, "{d}");
+        }
+        assert!(Distance::Euclidean.distance(&v, &near).unwrap() > 0.0);
+        assert!(Distance::Manhattan.distance(&v, &near).unwrap() > 0.0);
+
+        // Zero vectors keep their cosine distance of 1 and non-finite data is still rejected
+        let zero = Vector::new(3);
+        assert_eq!(Distance::CosineSim.distance(&zero, &zero).unwrap(), 1.0);
+        let nan = Vector::from_slice(&[f32::NAN, 1.0]);
+        assert!(matches!(Distance::Euclidean.distance(&nan, &nan), Err(VectorError::NonFiniteComponent { index: 0 })));
+    }
+
+    #[test]
+    fn test_distance_lp_overflow_errors() {
+        let v1 = Vector::from_slice(&[1e30, 1e30, 1e30, 1e30]);
+        let v2 = Vector::from_slice(&[-1e30, -1e30, -1e30, -1e30]);
+        assert!(matches!(Distance::Euclidean.distance(&v1, &v2), Err(VectorError::NumericalOverflow(Distance::Euclidean))));
+
+        let huge = Vector::from_slice(&[f32::MAX, f32::MAX]);
+        let negative_huge = Vector::from_slice(&[-f32::MAX, -f32::MAX]);
+        assert!(matches!(Distance::Manhattan.distance(&huge, &negative_huge), Err(VectorError::NumericalOverflow(Distance::Manhattan))));
+
+        // The f64 path stays finite for the same inputs
+        let wide = Distance::Euclidean.distance_f64(&v1, &v2).unwrap();
+        assert!((wide - 4e30).abs() / 4e30 < 1e-6);
+        assert!(Distance::Manhattan.distance_f64(&huge, &negative_huge).unwrap().is_finite());
+
+        assert_eq!(Distance::Dice.distance(&v1, &v2).unwrap(), 0.0);
+
+        // Non-finite inputs are not an overflow
+        let infinite = Vector::from_slice(&[1.0, f32::INFINITY]);
+        assert!(matches!(Distance::Manhattan.distance(&huge, &infinite), Err(VectorError::NonFiniteComponent { index: 1 })));
+    }
+
+    #[test]
+    fn test_distance_prefix() {
+        let v1 = Vector::from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        let v2 = Vector::from_slice(&[2.0, 0.0, 3.5, -1.0]);
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            assert_eq!(d.distance_prefix(&v1, &v2, 4).unwrap(), d.distance(&v1, &v2).unwrap());
+        }
+
+        let prefix = Distance::Euclidean.distance_prefix(&v1, &v2, 2).unwrap();
+        assert!(prefix < Distance::Euclidean.distance(&v1, &v2).unwrap());
+        assert!((prefix - 5.0f32.sqrt()).abs() < 1e-6);
+        assert_eq!(Distance::Euclidean.distance_prefix(&v1, &v2, 0).unwrap(), 0.0);
+
+        let short = Vector::from_slice(&[1.0, 2.0]);
+        assert!(matches!(
+            Distance::Euclidean.distance_prefix(&v1, &short, 3),
+            Err(VectorError::DimensionsMismatch { expected: 3, found: 2 })
+        ));
+        assert_eq!(Distance::Euclidean.distance_prefix(&v1, &short, 2).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_distance_f64_matches_f32() {
+        let v1 = Vector::from_slice(&[1.0, -2.0, 0.0, 4.5]);
+        let v2 = Vector::from_slice(&[0.5, 3.0, 0.0, -1.0]);
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            let wide = d.distance_f64(&v1, &v2).unwrap();
+            assert!((wide - d.distance(&v1, &v2).unwrap() as f64).abs() < 1e-5, "{d}");
+        }
+        assert!(Distance::Euclidean.distance_f64(&v1, &Vector::from_slice(&[1.0])).is_err());
+    }
+
+    #[test]
+    fn test_distance_recommend() {
+        let normalized = [
+            Vector::from_slice(&[0.6, 0.8]),
+            Vector::from_slice(&[1.0, 0.0]),
+            Vector::from_slice(&[0.0, -1.0]),
+        ];
+        assert_eq!(Distance::recommend(&normalized), Distance::CosineSim);
+
+        let unnormalized = [
+            Vector::from_slice(&[12.5, -3.0]),
+            Vector::from_slice(&[0.01, 250.0]),
+            Vector::from_slice(&[-40.0, 7.25]),
+        ];
+        assert_eq!(Distance::recommend(&unnormalized), Distance::Euclidean);
+
+        let counts = [
+            Vector::from_slice(&[3.0, 0.0, 12.0]),
+            Vector::from_slice(&[0.0, 5.0, 1.0]),
+        ];
+        assert_eq!(Distance::recommend(&counts), Distance::Manhattan);
+        assert_eq!(Distance::recommend(&[]), Distance::Euclidean);
+    }
+
+    #[test]
+    fn test_distance_lower_is_closer() {
+        let x = Vector::from_slice(&[1.0, 2.0, 0.0, 3.0]);
+        let y = Vector::from_slice(&[-2.0, 0.0, 5.0, -1.0]);
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            assert!(!d.is_similarity());
+            let same = d.distance(&x, &x).unwrap();
+            assert!(same.abs() < 1e-6, "{d}");
+            assert!(same <= d.distance(&x, &y).unwrap(), "{d}");
+        }
+    }
+
+    #[test]
+    fn test_distance_name_round_trip() {
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            assert_eq!(Distance::from_name(d.name()), Some(d));
+        }
+        assert_eq!("cp".parse::<Distance>(), Ok(Distance::CosinePositive));
+        assert_eq!("sorensen".parse::<Distance>(), Ok(Distance::Dice));
+    }
+
     #[test]
     fn test_distance_dimension_mismatch_errors() {
         let d = Distance::Euclidean;
@@ -126,4 +671,53 @@ mod tests {
         let result = d.distance(&v1, &v2);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_mahalanobis_identity_matches_euclidean() {
+        let m = Mahalanobis { inv_cov: vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]] };
+        let v1 = Vector::from_slice(&[1.0, 2.0, 3.0]);
+        let v2 = Vector::from_slice(&[4.0, 0.0, -1.0]);
+        let expected = Distance::Euclidean.distance(&v1, &v2).unwrap();
+        assert!((m.distance(&v1, &v2).unwrap() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mahalanobis_distance_calculation() {
+        let m = Mahalanobis { inv_cov: vec![vec![2.0, 1.0], vec![1.0, 2.0]] };
+        let v1 = Vector::from_slice(&[2.0, 3.0]);
+        let v2 = Vector::from_slice(&[1.0, 2.0]);
+        // diff = [1, 1], diff^T M diff = 2 + 1 + 1 + 2 = 6
+        assert!((m.distance(&v1, &v2).unwrap() - 6.0f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ensemble_metric_single_component_matches_metric() {
+        let v1 = Vector::from_slice(&[1.0, 2.0, 0.0]);
+        let v2 = Vector::from_slice(&[-1.0, 0.5, 3.0]);
+        for d in [Distance::Euclidean, Distance::Manhattan, Distance::CosineSim, Distance::CosinePositive, Distance::Dice] {
+            let ensemble = EnsembleMetric { components: vec![(d, 1.0)] };
+            assert_eq!(Metric::distance(&ensemble, &v1, &v2).unwrap(), d.distance(&v1, &v2).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_ensemble_metric_weighted_sum() {
+        let v1 = Vector::from_slice(&[1.0, 0.0]);
+        let v2 = Vector::from_slice(&[0.0, 1.0]);
+        let ensemble = EnsembleMetric { components: vec![(Distance::CosineSim, 0.7), (Distance::Manhattan, 0.3)] };
+        assert!((Metric::distance(&ensemble, &v1, &v2).unwrap() - (0.7 + 0.6)).abs() < 1e-6);
+        assert!(Metric::distance(&ensemble, &v1, &Vector::from_slice(&[1.0])).is_err());
+    }
+
+    #[test]
+    fn test_mahalanobis_dimension_mismatch_errors() {
+        let v1 = Vector::from_slice(&[1.0, 2.0]);
+        let v2 = Vector::from_slice(&[3.0, 4.0]);
+
+        let too_small = Mahalanobis { inv_cov: vec![vec![1.0]] };
+        assert!(too_small.distance(&v1, &v2).is_err());
+
+        let ragged = Mahalanobis { inv_cov: vec![vec![1.0, 0.0], vec![0.0]] };
+        assert!(ragged.distance(&v1, &v2).is_err());
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,105 @@
+use crate::core::vector::Vector;
+
+/// Vector with a compile-time dimension, operations need no runtime length checks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedVector<const N: usize>(pub [f32; N]);
+
+impl<const N: usize> FixedVector<N> {
+    /// Create a new fixed vector initialized to zeros
+    pub fn new() -> Self {
+        Self([0.0; N])
+    }
+
+    /// Get a reference to the underlying data
+    pub fn data(&self) -> &[f32; N] {
+        &self.0
+    }
+
+    /// Compute the dot product of two vectors
+    pub fn dot_product(&self, other: &Self) -> f32 {
+        let mut sum = 0.0;
+        for i in 0..N {
+            sum += self.0[i] * other.0[i];
+        }
+        sum
+    }
+
+    /// Compute the L2 norm (magnitude) of the vector
+    pub fn norm(&self) -> f32 {
+        self.dot_product(self).sqrt()
+    }
+
+    /// Euclidean distance, same as `Distance::Euclidean`
+    pub fn euclidean_distance(&self, other: &Self) -> f32 {
+        let mut sum = 0.0;
+        for i in 0..N {
+            let diff = self.0[i] - other.0[i];
+            sum += diff * diff;
+        }
+        sum.sqrt()
+    }
+
+    /// Cosine distance, same as `Distance::CosineSim`
+    pub fn cosine_distance(&self, other: &Self) -> f32 {
+        if N == 0 {
+            return 0.0;
+        }
+
+        let norm1 = self.norm();
+        let norm2 = other.norm();
+        if norm1 == 0.0 || norm2 == 0.0 {
+            return 1.0;
+        }
+
+        1.0 - (self.dot_product(other) / (norm1 * norm2)).clamp(-1.0, 1.0)
+    }
+}
+
+impl<const N: usize> Default for FixedVector<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> From<[f32; N]> for FixedVector<N> {
+    fn from(data: [f32; N]) -> Self {
+        Self(data)
+    }
+}
+
+impl<const N: usize> From<FixedVector<N>> for Vector {
+    fn from(vector: FixedVector<N>) -> Self {
+        Vector::from_slice(&vector.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Distance;
+
+    #[test]
+    fn test_fixed_vector_matches_dynamic_vector() {
+        let a = FixedVector([1.0, -2.0, 3.0]);
+        let b = FixedVector([4.0, 0.5, -1.0]);
+        let dynamic_a = Vector::from(a);
+        let dynamic_b = Vector::from(b);
+
+        assert_eq!(dynamic_a.data(), a.data());
+        assert_eq!(a.dot_product(&b), dynamic_a.dot_product(&dynamic_b).unwrap());
+        assert_eq!(a.norm(), dynamic_a.norm());
+        assert_eq!(a.euclidean_distance(&b), Distance::Euclidean.distance(&dynamic_a, &dynamic_b).unwrap());
+        assert_eq!(a.cosine_distance(&b), Distance::CosineSim.distance(&dynamic_a, &dynamic_b).unwrap());
+    }
+
+    #[test]
+    fn test_fixed_vector_edge_cases() {
+        let zero = FixedVector::<3>::new();
+        let a = FixedVector([1.0, 0.0, 0.0]);
+        assert_eq!(a.cosine_distance(&zero), 1.0);
+
+        let empty = FixedVector::<0>::new();
+        assert_eq!(empty.euclidean_distance(&empty), 0.0);
+        assert_eq!(empty.cosine_distance(&empty), 0.0);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::core::{KDTree, LSHIndex, Vector};
+use crate::error::VectorError;
+
+/// Common interface over searchable indexes, so different backends can be held as `Box<dyn Index>`
+pub trait Index {
+    /// Insert a vector under a key
+    fn insert(&mut self, vector: Vector, key: String) -> Result<(), VectorError>;
+
+    /// Find the k nearest neighbors of a query, closest first
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError>;
+
+    /// Get the number of live vectors
+    fn len(&self) -> usize;
+
+    /// Check if the index holds no live vectors
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Index for KDTree {
+    fn insert(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
+        KDTree::insert(self, vector, key)
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
+        self.nearest_neighbors(query, k)
+    }
+
+    fn len(&self) -> usize {
+        self.size()
+    }
+}
+
+impl Index for LSHIndex {
+    fn insert(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
+        LSHIndex::insert(self, vector, key)
+    }
+
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
+        self.nearest_neighbors(query, k)
+    }
+
+    fn len(&self) -> usize {
+        self.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Distance;
+    use crate::{BackingStorage, VectorDatabase};
+
+    #[test]
+    fn test_index_trait_objects_share_results() {
+        let mut indexes: Vec<Box<dyn Index>> = vec![
+            Box::new(KDTree::new(2, Distance::Euclidean).unwrap()),
+            Box::new(LSHIndex::with_seed(2, 6, 2, Distance::Euclidean, 4.0, 11).unwrap()),
+            Box::new(VectorDatabase::new(2, BackingStorage::KDTreeOnly, None)),
+        ];
+        assert!(indexes.iter().all(|index| index.is_empty()));
+
+        for i in 0..60 {
+            let value = i as f32 * 0.3;
+            let vector = Vector::from_slice(&[value.cos() * 5.0, value.sin() * 5.0 + i as f32 * 0.1]);
+            for index in &mut indexes {
+                index.insert(vector.clone(), format!("key{i}")).unwrap();
+            }
+        }
+        assert!(indexes.iter().all(|index| index.len() == 60));
+
+        let query = Vector::from_slice(&[4.0, 2.0]);
+        let exact = indexes[0].search(&query, 5).unwrap();
+        let approximate = indexes[1].search(&query, 5).unwrap();
+        assert_eq!(indexes[2].search(&query, 5).unwrap(), exact);
+        assert!(approximate.iter().any(|result| exact.contains(result)));
+    }
+}
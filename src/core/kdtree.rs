@@ -1,8 +1,9 @@
 use crate::core::{Vector, Distance};
+use crate::core::ordered_f32::{push_bounded, sort_ranked};
 use crate::error::VectorError;
-use std::collections::{HashMap, BinaryHeap};
-use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
+#[derive(Clone)]
 struct KDTreeNode {
     vector: Vector,
     key: String,
@@ -21,6 +22,20 @@ impl KDTreeNode {
     }
 }
 
+/// State threaded through a single k-nearest neighbors search
+struct NeighborSearch<'a> {
+    query: &'a [f32],
+    k: usize,
+    exclude: &'a HashSet<String>,
+    max_visits: Option<usize>,
+    /// Hits farther than this are never kept, it also bounds pruning until k hits are held
+    radius: f32,
+    /// Best hits so far as a max-heap from `push_bounded`, so the first one is the worst of the current k
+    results: &'a mut Vec<(String, f32)>,
+    visited: usize,
+}
+
+#[derive(Clone)]
 pub struct KDTree {
     root: Option<Box<KDTreeNode>>,
     dimensions: usize,
@@ -30,14 +45,18 @@ pub struct KDTree {
 }
 
 impl KDTree {
-    pub fn new(dimensions: usize, distance_metric: Distance) -> Self {
-        Self {
+    /// Create a new KD-tree, the metric must be coordinate-separable for the pruning bound to hold
+    pub fn new(dimensions: usize, distance_metric: Distance) -> Result<Self, VectorError> {
+        if !distance_metric.is_coordinate_separable() {
+            return Err(VectorError::UnsupportedMetric(distance_metric));
+        }
+        Ok(Self {
             root: None,
             dimensions,
             distance_metric,
             vector_map: HashMap::new(),
             tombstones: HashMap::new(),
-        }
+        })
     }
 
     /// Insert a vector into the KD-tree
@@ -68,7 +87,7 @@ impl KDTree {
     fn insert_recursive_static(node: &mut Box<KDTreeNode>, vector: Vector, key: String, depth: usize, dimensions: usize) {
         let split_dim = depth % dimensions;
         
-        let comparison = vector[split_dim].partial_cmp(&node.vector[split_dim]).unwrap();
+        let comparison = vector[split_dim].total_cmp(&node.vector[split_dim]);
         
         match comparison {
             std::cmp::Ordering::Less => {
@@ -100,6 +119,40 @@ impl KDTree {
         }
     }
 
+    /// Remove every vector and tombstone from the tree
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.vector_map.clear();
+        self.tombstones.clear();
+    }
+
+    /// Rebuild the tree to drop tombstoned nodes
+    pub fn compact(&mut self) {
+        self.rebuild_tree();
+    }
+
+    /// Release spare capacity held by the key maps
+    pub fn shrink_to_fit(&mut self) {
+        self.vector_map.shrink_to_fit();
+        self.tombstones.shrink_to_fit();
+    }
+
+    /// Estimate the heap memory used by the tree in bytes, ignoring allocator overhead
+    pub fn memory_usage(&self) -> usize {
+        let entry_size = std::mem::size_of::<(String, Vector)>();
+        let map_bytes = (self.vector_map.capacity() + self.tombstones.capacity()) * entry_size;
+        
+        // Every live or tombstoned entry is stored once in a map and once in a tree node
+        let entry_bytes: usize = self.vector_map
+            .iter()
+            .chain(self.tombstones.iter())
+            .map(|(key, vector)| 2 * (key.capacity() + std::mem::size_of_val(vector.data())))
+            .sum();
+        let node_bytes = (self.vector_map.len() + self.tombstones.len()) * std::mem::size_of::<KDTreeNode>();
+        
+        map_bytes + entry_bytes + node_bytes
+    }
+
     /// Rebuild the entire tree from the current vector_map
     fn rebuild_tree(&mut self) {
         // Filter out tombstoned entries and collect non-tombstoned vectors
@@ -112,6 +165,8 @@ impl KDTree {
         }
 
         // Clear tombstones when rebuilding
+        #[cfg(feature = "tracing")]
+        tracing::debug!(vectors = vectors.len(), tombstones = self.tombstones.len(), "rebuilding kd-tree");
         self.tombstones.clear();
         
         if vectors.is_empty() {
@@ -132,7 +187,7 @@ impl KDTree {
         let split_dim = depth % dimensions;
         
         // Sort by the split dimension
-        vectors.sort_by(|a, b| a.1[split_dim].partial_cmp(&b.1[split_dim]).unwrap());
+        vectors.sort_by(|a, b| a.1[split_dim].total_cmp(&b.1[split_dim]));
         
         let median_idx = vectors.len() / 2;
         let (key, vector) = vectors[median_idx].clone();
@@ -161,47 +216,87 @@ impl KDTree {
 
     /// Find k nearest neighbors to a query vector
     pub fn nearest_neighbors(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
-        if query.size() != self.dimensions {
-            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() });
+        self.nearest_neighbors_excluding(query, k, &HashSet::new())
+    }
+
+    /// Find k nearest neighbors to a query vector, skipping the excluded keys during the search
+    pub fn nearest_neighbors_excluding(&self, query: &Vector, k: usize, exclude: &HashSet<String>) -> Result<Vec<(String, f32)>, VectorError> {
+        self.nearest_neighbors_capped(query, k, exclude, None)
+    }
+
+    /// Find k nearest neighbors, stopping after `max_visits` nodes and returning the best found so far.
+    /// The cap makes the result approximate but deterministic for a given tree.
+    pub fn nearest_neighbors_capped(
+        &self,
+        query: &Vector,
+        k: usize,
+        exclude: &HashSet<String>,
+        max_visits: Option<usize>,
+    ) -> Result<Vec<(String, f32)>, VectorError> {
+        let mut results = Vec::new();
+        self.nearest_neighbors_counted(query.data(), k, exclude, max_visits, f32::INFINITY, &mut results)?;
+        Ok(results)
+    }
+
+    /// Capped k-nearest neighbors search over a query slice, keeping only hits within `radius`, that writes
+    /// the hits straight into `out`, which is cleared first, and returns the number of nodes visited
+    pub(crate) fn nearest_neighbors_counted(
+        &self,
+        query: &[f32],
+        k: usize,
+        exclude: &HashSet<String>,
+        max_visits: Option<usize>,
+        radius: f32,
+        out: &mut Vec<(String, f32)>,
+    ) -> Result<usize, VectorError> {
+        out.clear();
+        if query.len() != self.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.len() });
+        }
+        if let Some(index) = query.iter().position(|x| !x.is_finite()) {
+            return Err(VectorError::NonFiniteComponent { index });
         }
         
         if self.root.is_none() || k == 0 {
-            return Ok(Vec::new());
+            return Ok(0);
         }
         
-        let mut heap = BinaryHeap::new();
-        self.nearest_neighbors_recursive(self.root.as_ref().unwrap(), query, k, &mut heap, 0)?;
+        let mut search = NeighborSearch {
+            query,
+            k,
+            exclude,
+            max_visits,
+            radius,
+            results: out,
+            visited: 0,
+        };
+        self.nearest_neighbors_recursive(self.root.as_ref().unwrap(), 0, &mut search)?;
+        sort_ranked(search.results);
+        
+        #[cfg(feature = "tracing")]
+        tracing::debug!(nodes_visited = search.visited, results = search.results.len(), "kd-tree search");
         
-        Ok(heap.into_sorted_vec()
-            .into_iter()
-            .rev()
-            .map(|Reverse((distance, key))| (key, distance.0))
-            .collect())
+        Ok(search.visited)
     }
 
     /// Recursive k-nearest neighbors search helper
-    fn nearest_neighbors_recursive(
-        &self,
-        node: &KDTreeNode,
-        query: &Vector,
-        k: usize,
-        heap: &mut BinaryHeap<Reverse<(FloatOrd, String)>>,
-        depth: usize,
-    ) -> Result<(), VectorError> {
-        let current_distance = self.distance_metric.distance(&node.vector, query)?;
-        
-        if !self.tombstones.contains_key(&node.key) {
-            let entry = Reverse((FloatOrd(current_distance), node.key.clone()));
-            if heap.len() < k {
-                heap.push(entry);
-            } else if FloatOrd(current_distance) < heap.peek().unwrap().0.0 {
-                heap.pop();
-                heap.push(entry);
-            }
+    fn nearest_neighbors_recursive(&self, node: &KDTreeNode, depth: usize, search: &mut NeighborSearch) -> Result<(), VectorError> {
+        if search.max_visits.is_some_and(|max_visits| search.visited >= max_visits) {
+            return Ok(());
+        }
+        search.visited += 1;
+        let current_distance = self.distance_metric.candidate_distance(node.vector.data(), search.query)?;
+        
+        if let Some(current_distance) = current_distance
+            && current_distance <= search.radius
+            && !self.tombstones.contains_key(&node.key)
+            && !search.exclude.contains(&node.key)
+        {
+            push_bounded(search.results, search.k, &node.key, current_distance);
         }
         
         let split_dim = depth % self.dimensions;
-        let query_val = query[split_dim];
+        let query_val = search.query[split_dim];
         let node_val = node.vector[split_dim];
         
         let (first_child, second_child) = if query_val < node_val {
@@ -211,16 +306,22 @@ impl KDTree {
         };
         
         if let Some(child) = first_child {
-            self.nearest_neighbors_recursive(child, query, k, heap, depth + 1)?;
+            self.nearest_neighbors_recursive(child, depth + 1, search)?;
         }
         
-        let split_distance = (query_val - node_val).abs();
-        let worst_distance = heap.peek().map(|r| r.0.0 .0).unwrap_or(f32::INFINITY);
+        // Distance along the split axis alone bounds the distance to anything on the far side
+        let split_distance = self.distance_metric.axis_distance(query_val, node_val).unwrap_or(0.0);
+        let worst_distance = if search.results.len() < search.k {
+            search.radius
+        } else {
+            search.results[0].1
+        };
         
-        if split_distance < worst_distance {
-            if let Some(child) = second_child {
-                self.nearest_neighbors_recursive(child, query, k, heap, depth + 1)?;
-            }
+        // A non-finite split value bounds nothing, so its far side is always searched.
+        // A far-side hit at exactly the worst distance can still win the tie on its key.
+        if (split_distance.is_nan() || split_distance <= worst_distance)
+            && let Some(child) = second_child {
+            self.nearest_neighbors_recursive(child, depth + 1, search)?;
         }
         Ok(())
     }
@@ -256,30 +357,14 @@ impl KDTree {
     }
 }
 
-#[derive(PartialEq)]
-struct FloatOrd(pub f32);
-
-impl Eq for FloatOrd {}
-
-impl Ord for FloatOrd {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.0.partial_cmp(&other.0).unwrap_or(std::cmp::Ordering::Equal)
-    }
-}
-
-impl PartialOrd for FloatOrd {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::OrderedF32;
 
     #[test]
     fn test_kdtree_basic_insertion() {
-        let mut tree = KDTree::new(3, Distance::Euclidean);
+        let mut tree = KDTree::new(3, Distance::Euclidean).unwrap();
         let vector = Vector::from_slice(&[1.0, 2.0, 3.0]);
         tree.insert(vector, "point1".to_string()).unwrap();
         assert_eq!(tree.size(), 1);
@@ -287,7 +372,7 @@ mod tests {
 
     #[test]
     fn test_kdtree_nearest_neighbor_search() {
-        let mut tree = KDTree::new(3, Distance::Euclidean);
+        let mut tree = KDTree::new(3, Distance::Euclidean).unwrap();
         tree.insert(Vector::from_slice(&[1.0, 2.0, 3.0]), "point1".to_string()).unwrap();
         tree.insert(Vector::from_slice(&[4.0, 5.0, 6.0]), "point2".to_string()).unwrap();
 
@@ -298,7 +383,7 @@ mod tests {
 
     #[test]
     fn test_kdtree_k_nearest_neighbors_search() {
-        let mut tree = KDTree::new(3, Distance::Euclidean);
+        let mut tree = KDTree::new(3, Distance::Euclidean).unwrap();
         tree.insert(Vector::from_slice(&[1.0, 2.0, 3.0]), "point1".to_string()).unwrap();
         tree.insert(Vector::from_slice(&[4.0, 5.0, 6.0]), "point2".to_string()).unwrap();
         tree.insert(Vector::from_slice(&[8.0, 9.0, 10.0]), "point3".to_string()).unwrap();
@@ -318,9 +403,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_kdtree_radius_bounds_the_search() {
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
+        let mut points = Vec::new();
+        for i in 0..200 {
+            let vector = Vector::from_slice(&[((i * 37) % 101) as f32, ((i * 11) % 89) as f32]);
+            points.push((format!("point{i}"), vector.clone()));
+            tree.insert(vector, format!("point{i}")).unwrap();
+        }
+        tree.rebuild_tree();
+
+        let query = Vector::from_slice(&[40.0, 30.0]);
+        let mut expected: Vec<(String, f32)> = points
+            .iter()
+            .map(|(key, vector)| (key.clone(), Distance::Euclidean.distance(&query, vector).unwrap()))
+            .filter(|(_, distance)| *distance <= 12.0)
+            .collect();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut results = Vec::new();
+        let visited = tree.nearest_neighbors_counted(query.data(), usize::MAX, &HashSet::new(), None, 12.0, &mut results).unwrap();
+        assert!(!expected.is_empty());
+        assert_eq!(results, expected);
+        assert!(visited < points.len() / 2);
+    }
+
+    #[test]
+    fn test_kdtree_k_nearest_neighbors_matches_brute_force() {
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
+        let mut points = Vec::new();
+        for i in 0..50 {
+            let vector = Vector::from_slice(&[((i * 37) % 23) as f32, ((i * 11) % 17) as f32]);
+            points.push((format!("point{i}"), vector.clone()));
+            tree.insert(vector, format!("point{i}")).unwrap();
+        }
+
+        let query = Vector::from_slice(&[7.3, 4.1]);
+        let mut expected: Vec<(String, f32)> = points
+            .iter()
+            .map(|(key, vector)| (key.clone(), Distance::Euclidean.distance(&query, vector).unwrap()))
+            .collect();
+        expected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        let results = tree.nearest_neighbors(&query, 10).unwrap();
+        assert_eq!(results.len(), 10);
+        for (result, expected) in results.iter().zip(&expected) {
+            assert!((result.1 - expected.1).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_kdtree_manhattan_matches_brute_force() {
+        let mut tree = KDTree::new(3, Distance::Manhattan).unwrap();
+        let mut points = Vec::new();
+        for i in 0..60 {
+            let vector = Vector::from_slice(&[((i * 13) % 19) as f32, ((i * 7) % 11) as f32, ((i * 5) % 23) as f32 * 0.5]);
+            points.push((format!("point{i}"), vector.clone()));
+            tree.insert(vector, format!("point{i}")).unwrap();
+        }
+
+        let query = Vector::from_slice(&[9.2, 3.7, 6.1]);
+        let mut expected: Vec<(String, f32)> = points
+            .iter()
+            .map(|(key, vector)| (key.clone(), Distance::Manhattan.distance(&query, vector).unwrap()))
+            .collect();
+        expected.sort_by_key(|(_, distance)| OrderedF32(*distance));
+
+        let results = tree.nearest_neighbors(&query, 8).unwrap();
+        assert_eq!(results.len(), 8);
+        for (result, expected) in results.iter().zip(&expected) {
+            assert!((result.1 - expected.1).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_kdtree_nearest_neighbors_excluding() {
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
+        for i in 0..10 {
+            tree.insert(Vector::from_slice(&[i as f32, 0.0]), format!("point{i}")).unwrap();
+        }
+
+        let exclude: HashSet<String> = ["point0", "point1"].iter().map(|key| key.to_string()).collect();
+        let results = tree.nearest_neighbors_excluding(&Vector::from_slice(&[0.0, 0.0]), 3, &exclude).unwrap();
+        let keys: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["point2", "point3", "point4"]);
+    }
+
+    #[test]
+    fn test_kdtree_nearest_neighbors_capped() {
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
+        for i in 0..100 {
+            tree.insert(Vector::from_slice(&[((i * 37) % 101) as f32, ((i * 53) % 97) as f32]), format!("point{i}")).unwrap();
+        }
+        tree.compact();
+
+        let query = Vector::from_slice(&[13.0, 71.0]);
+        let exact = tree.nearest_neighbors(&query, 5).unwrap();
+
+        let uncapped = tree.nearest_neighbors_capped(&query, 5, &HashSet::new(), Some(1000)).unwrap();
+        assert_eq!(uncapped, exact);
+
+        let capped = tree.nearest_neighbors_capped(&query, 5, &HashSet::new(), Some(3)).unwrap();
+        assert!(capped.len() <= 3);
+        assert!(capped[0].1 >= exact[0].1);
+        assert_eq!(capped, tree.nearest_neighbors_capped(&query, 5, &HashSet::new(), Some(3)).unwrap());
+    }
+
+    #[test]
+    fn test_kdtree_rejects_non_separable_metrics() {
+        let result = KDTree::new(2, Distance::CosineSim);
+        assert!(matches!(result, Err(VectorError::UnsupportedMetric(Distance::CosineSim))));
+        assert!(KDTree::new(2, Distance::CosinePositive).is_err());
+        assert!(KDTree::new(2, Distance::Dice).is_err());
+    }
+
     #[test]
     fn test_kdtree_tombstone_and_rebuild_mechanism() {
-        let mut tree = KDTree::new(3, Distance::Euclidean);
+        let mut tree = KDTree::new(3, Distance::Euclidean).unwrap();
         tree.insert(Vector::from_slice(&[1.0, 2.0, 3.0]), "point1".to_string()).unwrap();
         tree.insert(Vector::from_slice(&[4.0, 5.0, 6.0]), "point2".to_string()).unwrap();
 
@@ -337,9 +537,28 @@ mod tests {
         assert!(!tree.is_tombstoned("point1"));
     }
 
+    #[test]
+    fn test_kdtree_compact_reduces_memory_usage() {
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
+        for i in 0..20 {
+            tree.insert(Vector::from_slice(&[i as f32, i as f32]), format!("point{i}")).unwrap();
+        }
+        for i in 0..8 {
+            tree.remove(&format!("point{i}"));
+        }
+        let before = tree.memory_usage();
+        assert_eq!(tree.tombstone_count(), 8);
+
+        tree.compact();
+        tree.shrink_to_fit();
+        assert_eq!(tree.tombstone_count(), 0);
+        assert_eq!(tree.size(), 12);
+        assert!(tree.memory_usage() < before);
+    }
+
     #[test]
     fn test_kdtree_tombstone_reinsert_behavior() {
-        let mut tree = KDTree::new(2, Distance::Euclidean);
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
         tree.insert(Vector::from_slice(&[1.0, 1.0]), "point1".to_string()).unwrap();
         tree.insert(Vector::from_slice(&[2.0, 2.0]), "point2".to_string()).unwrap();
         tree.remove("point1");
@@ -354,7 +573,7 @@ mod tests {
 
     #[test]
     fn test_kdtree_automatic_rebuild_trigger() {
-        let mut tree = KDTree::new(2, Distance::Euclidean);
+        let mut tree = KDTree::new(2, Distance::Euclidean).unwrap();
         
         // Add 3 points
         tree.insert(Vector::from_slice(&[1.0, 1.0]), "point1".to_string()).unwrap();
@@ -372,7 +591,7 @@ mod tests {
 
     #[test]
     fn test_kdtree_dimension_mismatch_errors() {
-        let mut tree = KDTree::new(3, Distance::Euclidean);
+        let mut tree = KDTree::new(3, Distance::Euclidean).unwrap();
         
         let vector_2d = Vector::from_slice(&[1.0, 2.0]);
         let result = tree.insert(vector_2d.clone(), "key1".to_string());
@@ -384,7 +603,7 @@ mod tests {
 
     #[test]
     fn test_kdtree_duplicate_key_prevention() {
-        let mut tree = KDTree::new(3, Distance::Euclidean);
+        let mut tree = KDTree::new(3, Distance::Euclidean).unwrap();
         tree.insert(Vector::from_slice(&[1.0, 2.0, 3.0]), "point1".to_string()).unwrap();
         let result = tree.insert(Vector::from_slice(&[4.0, 5.0, 6.0]), "point1".to_string());
         assert!(result.is_err());
@@ -1,8 +1,10 @@
 use crate::core::{Vector, Distance};
+use crate::core::ordered_f32::{push_bounded, sort_ranked};
 use crate::error::VectorError;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rand_distr::{Normal, Cauchy, StandardNormal};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Enum for different hash function types
 #[derive(Debug, Clone)]
@@ -13,7 +15,7 @@ enum HashFunction {
 }
 
 impl HashFunction {
-    fn hash(&self, v: &Vector) -> Result<isize, VectorError> {
+    fn hash(&self, v: &[f32]) -> Result<isize, VectorError> {
         match self {
             HashFunction::Euclidean(hf) => hf.hash(v),
             HashFunction::Manhattan(hf) => hf.hash(v),
@@ -22,12 +24,22 @@ impl HashFunction {
     }
 }
 
+/// Dot product of a hash function's random vector with a slice of components
+fn project(random_vector: &Vector, v: &[f32]) -> Result<f32, VectorError> {
+    if random_vector.size() != v.len() {
+        return Err(VectorError::DimensionsMismatch { expected: random_vector.size(), found: v.len() });
+    }
+    Ok(random_vector.data().iter().zip(v).map(|(a, b)| a * b).sum())
+}
+
 impl Distance {
-    fn create_hash_function(&self, dims: usize, width: f32) -> HashFunction {
+    /// Hash family for the metric, Dice has no locality-sensitive family here
+    fn create_hash_function(&self, dims: usize, width: f32, rng: &mut StdRng) -> Result<HashFunction, VectorError> {
         match self {
-            Distance::Euclidean => HashFunction::Euclidean(EuclideanHashFunction::new(dims, width)),
-            Distance::Manhattan => HashFunction::Manhattan(ManhattanHashFunction::new(dims, width)),
-            Distance::CosineSim => HashFunction::CosineSim(CosineSimHashFunction::new(dims)),
+            Distance::Euclidean => Ok(HashFunction::Euclidean(EuclideanHashFunction::new(dims, width, rng))),
+            Distance::Manhattan => Ok(HashFunction::Manhattan(ManhattanHashFunction::new(dims, width, rng))),
+            Distance::CosineSim | Distance::CosinePositive => Ok(HashFunction::CosineSim(CosineSimHashFunction::new(dims, rng))),
+            Distance::Dice => Err(VectorError::UnsupportedMetric(*self)),
         }
     }
 }
@@ -41,8 +53,7 @@ struct EuclideanHashFunction {
 }
 
 impl EuclideanHashFunction {
-    fn new(dims: usize, width: f32) -> Self {
-        let mut rng = rand::thread_rng();
+    fn new(dims: usize, width: f32, rng: &mut StdRng) -> Self {
         let normal = Normal::new(0.0, 1.0).unwrap();
         
         let mut random_vector = Vector::new(dims);
@@ -61,8 +72,8 @@ impl EuclideanHashFunction {
 }
 
 impl EuclideanHashFunction {
-    fn hash(&self, v: &Vector) -> Result<isize, VectorError> {
-        let projection = self.random_vector.dot_product(v)? + self.bias;
+    fn hash(&self, v: &[f32]) -> Result<isize, VectorError> {
+        let projection = project(&self.random_vector, v)? + self.bias;
         
         Ok((projection / self.width).floor() as isize)
     }
@@ -77,8 +88,7 @@ struct ManhattanHashFunction {
 }
 
 impl ManhattanHashFunction {
-    fn new(dims: usize, width: f32) -> Self {
-        let mut rng = rand::thread_rng();
+    fn new(dims: usize, width: f32, rng: &mut StdRng) -> Self {
         let cauchy = Cauchy::new(0.0, 1.0).unwrap();
         
         let mut random_vector = Vector::new(dims);
@@ -97,8 +107,8 @@ impl ManhattanHashFunction {
 }
 
 impl ManhattanHashFunction {
-    fn hash(&self, v: &Vector) -> Result<isize, VectorError> {
-        let projection = self.random_vector.dot_product(v)? + self.bias;
+    fn hash(&self, v: &[f32]) -> Result<isize, VectorError> {
+        let projection = project(&self.random_vector, v)? + self.bias;
         
         Ok((projection / self.width).floor() as isize)
     }
@@ -111,8 +121,7 @@ struct CosineSimHashFunction {
 }
 
 impl CosineSimHashFunction {
-    fn new(dims: usize) -> Self {
-        let mut rng = rand::thread_rng();
+    fn new(dims: usize, rng: &mut StdRng) -> Self {
         let normal = StandardNormal;
         
         let mut random_vector = Vector::new(dims);
@@ -125,8 +134,8 @@ impl CosineSimHashFunction {
 }
 
 impl CosineSimHashFunction {
-    fn hash(&self, v: &Vector) -> Result<isize, VectorError> {
-        if self.random_vector.dot_product(v)? >= 0.0 {
+    fn hash(&self, v: &[f32]) -> Result<isize, VectorError> {
+        if project(&self.random_vector, v)? >= 0.0 {
             Ok(1)
         } else {
             Ok(0)
@@ -135,6 +144,7 @@ impl CosineSimHashFunction {
 }
 
 /// LSH index for approximate nearest neighbor search
+#[derive(Clone)]
 pub struct LSHIndex {
     hash_functions: Vec<Vec<HashFunction>>,
     hash_tables: Vec<HashMap<isize, HashMap<String, Vector>>>,
@@ -144,32 +154,57 @@ pub struct LSHIndex {
 }
 
 impl LSHIndex {
-    /// Create a new LSH index
+    /// Create a new LSH index with randomly seeded hash functions, Dice distance is rejected
     pub fn new(
         dimensions: usize,
         num_tables: usize,
         num_hash_functions: usize,
         distance_metric: Distance,
         width: f32,
-    ) -> Self {
+    ) -> Result<Self, VectorError> {
+        Self::with_rng(dimensions, num_tables, num_hash_functions, distance_metric, width, StdRng::from_entropy())
+    }
+
+    /// Create a new LSH index whose hash functions are fully determined by `seed`
+    pub fn with_seed(
+        dimensions: usize,
+        num_tables: usize,
+        num_hash_functions: usize,
+        distance_metric: Distance,
+        width: f32,
+        seed: u64,
+    ) -> Result<Self, VectorError> {
+        Self::with_rng(dimensions, num_tables, num_hash_functions, distance_metric, width, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(
+        dimensions: usize,
+        num_tables: usize,
+        num_hash_functions: usize,
+        distance_metric: Distance,
+        width: f32,
+        mut rng: StdRng,
+    ) -> Result<Self, VectorError> {
         let mut hash_functions = Vec::with_capacity(num_tables);
         let mut hash_tables = Vec::with_capacity(num_tables);
         for _ in 0..num_tables {
             let mut table_functions = Vec::with_capacity(num_hash_functions);
             for _ in 0..num_hash_functions {
-                let hash_function = distance_metric.create_hash_function(dimensions, width);
+                let hash_function = distance_metric.create_hash_function(dimensions, width, &mut rng)?;
                 table_functions.push(hash_function);
             }
             hash_functions.push(table_functions);
             hash_tables.push(HashMap::new());
         }
-        Self {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(dimensions, num_tables, num_hash_functions, "created lsh index");
+        Ok(Self {
             hash_functions,
             hash_tables,
             vector_map: HashMap::new(),
             dimensions,
             distance_metric
-        }
+        })
     }
     
     /// Insert a vector into the LSH index
@@ -184,7 +219,7 @@ impl LSHIndex {
         for (table_idx, table_functions) in self.hash_functions.iter().enumerate() {
             let mut hash_values = Vec::with_capacity(table_functions.len());
             for hash_function in table_functions {
-                hash_values.push(hash_function.hash(&vector)?);
+                hash_values.push(hash_function.hash(vector.data())?);
             }
             let bucket_key = self.combine_hashes(&hash_values);
             self.hash_tables[table_idx]
@@ -210,13 +245,100 @@ impl LSHIndex {
         }
     }
 
+    /// Remove every vector from the index, keeping its hash functions
+    pub fn clear(&mut self) {
+        self.vector_map.clear();
+        for table in &mut self.hash_tables {
+            table.clear();
+        }
+    }
+
+    /// Drop buckets left empty by removals
+    pub fn compact(&mut self) {
+        for table in &mut self.hash_tables {
+            table.retain(|_, bucket| !bucket.is_empty());
+        }
+    }
+
+    /// Release spare capacity held by the key map and hash tables
+    pub fn shrink_to_fit(&mut self) {
+        self.vector_map.shrink_to_fit();
+        for table in &mut self.hash_tables {
+            for bucket in table.values_mut() {
+                bucket.shrink_to_fit();
+            }
+            table.shrink_to_fit();
+        }
+    }
+
+    /// Estimate the heap memory used by the index in bytes, ignoring allocator overhead
+    pub fn memory_usage(&self) -> usize {
+        let entry_size = std::mem::size_of::<(String, Vector)>();
+        let bucket_size = std::mem::size_of::<(isize, HashMap<String, Vector>)>();
+        let vector_bytes = |(key, vector): (&String, &Vector)| key.capacity() + std::mem::size_of_val(vector.data());
+        
+        let map_bytes = self.vector_map.capacity() * entry_size
+            + self.vector_map.iter().map(vector_bytes).sum::<usize>();
+        
+        // Each table stores its own copy of every key and vector
+        let table_bytes: usize = self.hash_tables
+            .iter()
+            .map(|table| {
+                table.capacity() * bucket_size
+                    + table
+                        .values()
+                        .map(|bucket| bucket.capacity() * entry_size + bucket.iter().map(vector_bytes).sum::<usize>())
+                        .sum::<usize>()
+            })
+            .sum();
+        
+        map_bytes + table_bytes
+    }
+
     /// Search for approximate nearest neighbors, no guarantee you will get all k nearest neighbors
     pub fn nearest_neighbors(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
-        if query.size() != self.dimensions {
-            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() });
+        self.nearest_neighbors_excluding(query, k, &HashSet::new())
+    }
+
+    /// Search for approximate nearest neighbors, skipping the excluded keys when collecting candidates
+    pub fn nearest_neighbors_excluding(&self, query: &Vector, k: usize, exclude: &HashSet<String>) -> Result<Vec<(String, f32)>, VectorError> {
+        self.nearest_neighbors_capped(query, k, exclude, None)
+    }
+
+    /// Search for approximate nearest neighbors probing at most `max_visits` tables, in table order
+    pub fn nearest_neighbors_capped(
+        &self,
+        query: &Vector,
+        k: usize,
+        exclude: &HashSet<String>,
+        max_visits: Option<usize>,
+    ) -> Result<Vec<(String, f32)>, VectorError> {
+        let mut results = Vec::new();
+        self.nearest_neighbors_counted(query.data(), k, exclude, max_visits, f32::INFINITY, &mut results)?;
+        Ok(results)
+    }
+
+    /// Capped approximate search over a query slice, keeping only hits within `radius`, that writes
+    /// the hits straight into `out`, which is cleared first, and returns the number of candidates scanned
+    pub(crate) fn nearest_neighbors_counted(
+        &self,
+        query: &[f32],
+        k: usize,
+        exclude: &HashSet<String>,
+        max_visits: Option<usize>,
+        radius: f32,
+        out: &mut Vec<(String, f32)>,
+    ) -> Result<usize, VectorError> {
+        out.clear();
+        if query.len() != self.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.len() });
+        }
+        if let Some(index) = query.iter().position(|x| !x.is_finite()) {
+            return Err(VectorError::NonFiniteComponent { index });
         }
+        let tables_probed = max_visits.map_or(self.hash_tables.len(), |max_visits| max_visits.min(self.hash_tables.len()));
         let mut candidates = HashMap::new();
-        for (table_idx, table_functions) in self.hash_functions.iter().enumerate() {
+        for (table_idx, table_functions) in self.hash_functions.iter().enumerate().take(tables_probed) {
             let mut hash_values = Vec::with_capacity(table_functions.len());
             for hash_function in table_functions {
                 hash_values.push(hash_function.hash(query)?);
@@ -224,20 +346,23 @@ impl LSHIndex {
             let bucket_key = self.combine_hashes(&hash_values);
             if let Some(bucket) = self.hash_tables[table_idx].get(&bucket_key) {
                 for (key, vector) in bucket {
-                    candidates.insert(key.clone(), vector.clone());
+                    if !exclude.contains(key) {
+                        candidates.insert(key, vector);
+                    }
                 }
             }
         }
-        let mut results: Vec<(String, f32)> = candidates
-            .iter()
-            .map(|(key, vector)| {
-                let distance = self.distance_metric.distance(query, vector).unwrap();
-                (key.clone(), distance)
-            })
-            .collect();
-        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-        results.truncate(k);
-        Ok(results)
+        #[cfg(feature = "tracing")]
+        tracing::debug!(tables_probed, candidates = candidates.len(), "lsh search");
+        for (key, vector) in &candidates {
+            if let Some(distance) = self.distance_metric.candidate_distance(vector.data(), query)?
+                && distance <= radius
+            {
+                push_bounded(out, k, key, distance);
+            }
+        }
+        sort_ranked(out);
+        Ok(candidates.len())
     }
     
     /// Combine multiple hash values into a single bucket key
@@ -251,11 +376,7 @@ impl LSHIndex {
     
     /// Get the hash type name
     pub fn hash_type_name(&self) -> &'static str {
-        match self.distance_metric {
-            Distance::Euclidean => "euclidean",
-            Distance::Manhattan => "manhattan",
-            Distance::CosineSim => "cosinesim",
-        }
+        self.distance_metric.name()
     }
 
     /// Get a vector by key
@@ -302,9 +423,10 @@ mod tests {
                     bias: 0.0,
                     width,
                 }),
-                Distance::CosineSim => HashFunction::CosineSim(CosineSimHashFunction {
+                Distance::CosineSim | Distance::CosinePositive => HashFunction::CosineSim(CosineSimHashFunction {
                     random_vector: vector.clone(),
                 }),
+                Distance::Dice => unreachable!("LSH has no hash family for Dice"),
             };
     
             let hash_functions = vec![vec![hash_function; 5]; 10];
@@ -348,11 +470,44 @@ mod tests {
         run_test(Distance::Euclidean);
         run_test(Distance::Manhattan);
         run_test(Distance::CosineSim);
+        run_test(Distance::CosinePositive);
+    }
+
+    #[test]
+    fn test_lsh_rejects_dice() {
+        assert!(matches!(LSHIndex::new(2, 4, 2, Distance::Dice, 1.0), Err(VectorError::UnsupportedMetric(Distance::Dice))));
+        assert!(matches!(LSHIndex::with_seed(2, 4, 2, Distance::Dice, 1.0, 7), Err(VectorError::UnsupportedMetric(Distance::Dice))));
+        assert!(LSHIndex::with_seed(2, 4, 2, Distance::CosineSim, 1.0, 7).is_ok());
     }
     
+    #[test]
+    fn test_lsh_same_seed_is_deterministic() {
+        let build = |seed| {
+            let mut lsh = LSHIndex::with_seed(3, 4, 3, Distance::Euclidean, 2.0, seed).unwrap();
+            for i in 0..30 {
+                let value = i as f32;
+                lsh.insert(Vector::from_slice(&[value.sin() * 5.0, value.cos() * 5.0, value * 0.1]), format!("key{i}")).unwrap();
+            }
+            lsh
+        };
+
+        let lsh1 = build(42);
+        let lsh2 = build(42);
+        assert_eq!(format!("{:?}", lsh1.hash_functions), format!("{:?}", lsh2.hash_functions));
+        for (table1, table2) in lsh1.hash_tables.iter().zip(&lsh2.hash_tables) {
+            assert_eq!(table1, table2);
+        }
+
+        let query = Vector::from_slice(&[1.0, 2.0, 0.5]);
+        assert_eq!(lsh1.nearest_neighbors(&query, 5).unwrap(), lsh2.nearest_neighbors(&query, 5).unwrap());
+
+        let lsh3 = build(7);
+        assert_ne!(format!("{:?}", lsh1.hash_functions), format!("{:?}", lsh3.hash_functions));
+    }
+
     #[test]
     fn test_lsh_dimension_mismatch_errors() {
-        let mut lsh = LSHIndex::new(3, 10, 5, Distance::Euclidean, 1.0);
+        let mut lsh = LSHIndex::new(3, 10, 5, Distance::Euclidean, 1.0).unwrap();
         
         let vector_2d = Vector::from_slice(&[1.0, 2.0]);
         let result = lsh.insert(vector_2d.clone(), "key1".to_string());
@@ -364,7 +519,7 @@ mod tests {
 
     #[test]
     fn test_lsh_duplicate_key_prevention() {
-        let mut lsh = LSHIndex::new(2, 10, 5, Distance::Euclidean, 1.0);
+        let mut lsh = LSHIndex::new(2, 10, 5, Distance::Euclidean, 1.0).unwrap();
         let vector = Vector::from_slice(&[1.0, 1.0]);
         lsh.insert(vector.clone(), "key1".to_string()).unwrap();
         let result = lsh.insert(vector, "key1".to_string());
@@ -373,7 +528,7 @@ mod tests {
 
     #[test]
     fn test_lsh_remove_and_cleanup() {
-        let mut lsh = LSHIndex::new(2, 10, 5, Distance::Euclidean, 1.0);
+        let mut lsh = LSHIndex::new(2, 10, 5, Distance::Euclidean, 1.0).unwrap();
         let vector = Vector::from_slice(&[1.0, 1.0]);
         lsh.insert(vector, "key1".to_string()).unwrap();
         assert!(lsh.vector_map.contains_key("key1"));
@@ -384,4 +539,61 @@ mod tests {
         let results = lsh.nearest_neighbors(&Vector::from_slice(&[1.0, 1.0]), 1).unwrap();
         assert!(results.is_empty());
     }
+
+    #[test]
+    fn test_lsh_nearest_neighbors_excluding() {
+        let mut lsh = LSHIndex::new(2, 4, 1, Distance::CosineSim, 1.0).unwrap();
+        lsh.insert(Vector::from_slice(&[1.0, 0.0]), "key1".to_string()).unwrap();
+        lsh.insert(Vector::from_slice(&[2.0, 0.0]), "key2".to_string()).unwrap();
+
+        let exclude: HashSet<String> = ["key1".to_string()].into_iter().collect();
+        let results = lsh.nearest_neighbors_excluding(&Vector::from_slice(&[1.0, 0.0]), 2, &exclude).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "key2");
+    }
+
+    #[test]
+    fn test_lsh_nearest_neighbors_capped() {
+        let mut lsh = LSHIndex::with_seed(2, 8, 2, Distance::Euclidean, 4.0, 3).unwrap();
+        for i in 0..50 {
+            let value = i as f32 * 0.4;
+            lsh.insert(Vector::from_slice(&[value.sin() * 3.0, value.cos() * 3.0]), format!("key{i}")).unwrap();
+        }
+
+        let query = Vector::from_slice(&[0.5, 2.5]);
+        let uncapped = lsh.nearest_neighbors(&query, 5).unwrap();
+        assert_eq!(lsh.nearest_neighbors_capped(&query, 5, &HashSet::new(), Some(100)).unwrap(), uncapped);
+        assert!(lsh.nearest_neighbors_capped(&query, 5, &HashSet::new(), Some(0)).unwrap().is_empty());
+
+        let capped = lsh.nearest_neighbors_capped(&query, 5, &HashSet::new(), Some(1)).unwrap();
+        assert!(capped.first().unwrap().1 >= uncapped.first().unwrap().1);
+
+        // A cap of one probes only the first table, so every hit comes from the query's bucket there
+        let hashes: Vec<isize> = lsh.hash_functions[0].iter().map(|function| function.hash(query.data()).unwrap()).collect();
+        let first_bucket = &lsh.hash_tables[0][&lsh.combine_hashes(&hashes)];
+        assert!(capped.iter().all(|(key, _)| first_bucket.contains_key(key)));
+
+        let mut out = Vec::new();
+        let scanned_capped = lsh.nearest_neighbors_counted(query.data(), 5, &HashSet::new(), Some(1), f32::INFINITY, &mut out).unwrap();
+        let scanned_uncapped = lsh.nearest_neighbors_counted(query.data(), 5, &HashSet::new(), None, f32::INFINITY, &mut out).unwrap();
+        assert_eq!(scanned_capped, first_bucket.len());
+        assert!(scanned_capped < scanned_uncapped);
+    }
+
+    #[test]
+    fn test_lsh_compact_reduces_memory_usage() {
+        let mut lsh = LSHIndex::new(2, 4, 2, Distance::Euclidean, 1.0).unwrap();
+        for i in 0..20 {
+            lsh.insert(Vector::from_slice(&[i as f32, -(i as f32)]), format!("key{i}")).unwrap();
+        }
+        let before = lsh.memory_usage();
+        for i in 0..15 {
+            lsh.remove(&format!("key{i}"));
+        }
+
+        lsh.compact();
+        lsh.shrink_to_fit();
+        assert!(lsh.hash_tables.iter().all(|table| table.values().all(|bucket| !bucket.is_empty())));
+        assert!(lsh.memory_usage() < before);
+    }
 } 
\ No newline at end of file
@@ -1,9 +1,17 @@
 pub mod vector;
+pub mod binary_code;
 pub mod distance;
+pub mod fixed_vector;
+pub mod index;
 pub mod kdtree;
 pub mod lsh;
+pub mod ordered_f32;
 
 pub use vector::Vector;
-pub use distance::Distance;
+pub use binary_code::BinaryCode;
+pub use distance::{Distance, EnsembleMetric, Mahalanobis, Metric};
+pub use fixed_vector::FixedVector;
+pub use index::Index;
 pub use kdtree::KDTree;
-pub use lsh::LSHIndex;
\ No newline at end of file
+pub use lsh::LSHIndex;
+pub use ordered_f32::OrderedF32;
\ No newline at end of file
@@ -0,0 +1,148 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// f32 wrapper with a total order from `f32::total_cmp`, positive NaN sorts after infinity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrderedF32(pub f32);
+
+impl PartialEq for OrderedF32 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedF32 {}
+
+impl Ord for OrderedF32 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for OrderedF32 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for OrderedF32 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // total_cmp equality is bitwise equality, so hashing the bits is consistent with Eq
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl From<f32> for OrderedF32 {
+    fn from(value: f32) -> Self {
+        OrderedF32(value)
+    }
+}
+
+impl From<OrderedF32> for f32 {
+    fn from(value: OrderedF32) -> Self {
+        value.0
+    }
+}
+
+/// Rank of a search hit, distance first with the key breaking ties
+fn rank(hit: &(String, f32)) -> (OrderedF32, &str) {
+    (OrderedF32(hit.1), hit.0.as_str())
+}
+
+/// Add a hit to `hits`, kept as a max-heap on rank holding at most the `k` best hits, so the
+/// worst one is at index 0. When the heap is full a hit must rank before the worst to be kept.
+pub(crate) fn push_bounded(hits: &mut Vec<(String, f32)>, k: usize, key: &str, distance: f32) {
+    if hits.len() < k {
+        hits.push((key.to_string(), distance));
+        let mut child = hits.len() - 1;
+        while child > 0 && rank(&hits[child]) > rank(&hits[(child - 1) / 2]) {
+            hits.swap(child, (child - 1) / 2);
+            child = (child - 1) / 2;
+        }
+        return;
+    }
+    if k == 0 || (OrderedF32(distance), key) >= rank(&hits[0]) {
+        return;
+    }
+
+    // Reuse the evicted key's allocation, then sift the new hit down to its place
+    hits[0].0.clear();
+    hits[0].0.push_str(key);
+    hits[0].1 = distance;
+    let mut parent = 0;
+    loop {
+        let mut largest = parent;
+        for child in [2 * parent + 1, 2 * parent + 2] {
+            if child < hits.len() && rank(&hits[child]) > rank(&hits[largest]) {
+                largest = child;
+            }
+        }
+        if largest == parent {
+            break;
+        }
+        hits.swap(parent, largest);
+        parent = largest;
+    }
+}
+
+/// Sort hits gathered with `push_bounded` into ranking order, closest first
+pub(crate) fn sort_ranked(hits: &mut [(String, f32)]) {
+    hits.sort_unstable_by(|a, b| rank(a).cmp(&rank(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered_f32_normal_ordering() {
+        let mut values: Vec<OrderedF32> = [3.0, -1.0, 2.5, 0.0].into_iter().map(OrderedF32::from).collect();
+        values.sort();
+        let sorted: Vec<f32> = values.into_iter().map(f32::from).collect();
+        assert_eq!(sorted, [-1.0, 0.0, 2.5, 3.0]);
+        assert!(OrderedF32(1.0) < OrderedF32(f32::INFINITY));
+    }
+
+    #[test]
+    fn test_ordered_f32_nan_sorts_to_end() {
+        let mut values: Vec<OrderedF32> = [f32::NAN, 1.0, f32::INFINITY, -2.0].into_iter().map(OrderedF32::from).collect();
+        values.sort();
+        assert_eq!(values[0].0, -2.0);
+        assert_eq!(values[1].0, 1.0);
+        assert_eq!(values[2].0, f32::INFINITY);
+        assert!(values[3].0.is_nan());
+        assert_eq!(OrderedF32(f32::NAN), OrderedF32(f32::NAN));
+    }
+
+    #[test]
+    fn test_ordered_f32_hash_matches_eq() {
+        use std::collections::HashSet;
+
+        let set: HashSet<OrderedF32> = [1.0, 1.0, f32::NAN, f32::NAN].into_iter().map(OrderedF32::from).collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_ordered_f32_push_bounded_keeps_k_best() {
+        let mut hits = Vec::with_capacity(3);
+        for (key, distance) in [("d", 4.0), ("b", 2.0), ("e", 5.0), ("c", 3.0), ("a", 2.0), ("f", 3.0)] {
+            push_bounded(&mut hits, 3, key, distance);
+        }
+        assert_eq!(hits[0], ("c".to_string(), 3.0));
+        sort_ranked(&mut hits);
+        assert_eq!(hits, [("a".to_string(), 2.0), ("b".to_string(), 2.0), ("c".to_string(), 3.0)]);
+        assert_eq!(hits.capacity(), 3);
+
+        // Ties at the k-th position go to the smaller key whatever the insertion order
+        let mut ties = Vec::new();
+        for key in ["e", "d", "c", "b", "a"] {
+            push_bounded(&mut ties, 2, key, 1.0);
+        }
+        sort_ranked(&mut ties);
+        assert_eq!(ties, [("a".to_string(), 1.0), ("b".to_string(), 1.0)]);
+
+        let mut empty = Vec::new();
+        push_bounded(&mut empty, 0, "a", 1.0);
+        assert!(empty.is_empty());
+    }
+}
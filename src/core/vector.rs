@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::ops::{Index, IndexMut};
 use std::hash::{Hash, Hasher};
+use crate::core::binary_code::BinaryCode;
 use crate::error::VectorError;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -56,11 +57,63 @@ impl Vector {
             .sum())
     }
 
+    /// Compute the dot product over only the dimensions where `mask` is true
+    pub fn dot_product_masked(&self, other: &Vector, mask: &[bool]) -> Result<f32, VectorError> {
+        if self.size() != other.size() {
+            return Err(VectorError::DimensionsMismatch { expected: self.size(), found: other.size() });
+        }
+        if self.size() != mask.len() {
+            return Err(VectorError::DimensionsMismatch { expected: self.size(), found: mask.len() });
+        }
+
+        Ok(self.data
+            .iter()
+            .zip(other.data.iter())
+            .zip(mask)
+            .filter(|(_, selected)| **selected)
+            .map(|((a, b), _)| a * b)
+            .sum())
+    }
+
     /// Compute the L2 norm (magnitude) of the vector
     pub fn norm(&self) -> f32 {
         self.data.iter().map(|x| x * x).sum::<f32>().sqrt()
     }
 
+    /// Get the largest absolute component value, 0.0 for an empty vector
+    pub fn max_abs_component(&self) -> f32 {
+        self.data.iter().fold(0.0, |max, x| max.max(x.abs()))
+    }
+
+    /// Get the smallest component value, 0.0 for an empty vector
+    pub fn min_component(&self) -> f32 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        self.data.iter().copied().fold(f32::INFINITY, f32::min)
+    }
+
+    /// Get the largest component value, 0.0 for an empty vector
+    pub fn max_component(&self) -> f32 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        self.data.iter().copied().fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// Get the sum of all components, 0.0 for an empty vector
+    pub fn sum(&self) -> f32 {
+        self.data.iter().sum()
+    }
+
+    /// Get the mean of all components, 0.0 for an empty vector
+    pub fn mean(&self) -> f32 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        self.sum() / self.size() as f32
+    }
+
     /// Normalize the vector to unit length, mutates the vector
     pub fn normalize(&mut self) {
         let norm = self.norm();
@@ -114,6 +167,93 @@ impl Vector {
             data: self.data.iter().map(|x| x * scalar).collect(),
         }
     }
+
+    /// Returns the projection of this vector onto `basis`, `(a . b / b . b) * b`
+    pub fn project_onto(&self, basis: &Vector) -> Result<Vector, VectorError> {
+        let basis_norm_squared = basis.dot_product(basis)?;
+        if basis_norm_squared == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        Ok(basis.scale(self.dot_product(basis)? / basis_norm_squared))
+    }
+
+    /// Returns the component of this vector orthogonal to `basis`, `a - project_onto(b)`
+    pub fn reject_from(&self, basis: &Vector) -> Result<Vector, VectorError> {
+        self.subtract(&self.project_onto(basis)?)
+    }
+
+    /// Returns the angle to `other` in radians, in [0, pi]
+    pub fn angle_between(&self, other: &Vector) -> Result<f32, VectorError> {
+        let dot = self.dot_product(other)?;
+        let (norm1, norm2) = (self.norm(), other.norm());
+        if norm1 == 0.0 || norm2 == 0.0 {
+            return Err(VectorError::ZeroVector);
+        }
+        Ok((dot / (norm1 * norm2)).clamp(-1.0, 1.0).acos())
+    }
+
+    /// Returns the angle to `other` in degrees, in [0, 180]
+    pub fn angle_between_degrees(&self, other: &Vector) -> Result<f32, VectorError> {
+        Ok(self.angle_between(other)?.to_degrees())
+    }
+
+    /// Returns the component-wise mean of the given vectors
+    pub fn centroid(vectors: &[Vector]) -> Result<Vector, VectorError> {
+        let first = vectors.first().ok_or(VectorError::EmptyInput)?;
+
+        let mut sum = Vector::new(first.size());
+        for vector in vectors {
+            sum = sum.add(vector)?;
+        }
+        Ok(sum.scale(1.0 / vectors.len() as f32))
+    }
+
+    /// Pack the sign of each component into a binary code for Hamming-distance pre-filtering
+    pub fn to_binary_code(&self) -> BinaryCode {
+        BinaryCode::from_components(&self.data)
+    }
+
+    /// Parse a vector from little-endian f32 bytes, the length must be a multiple of 4
+    pub fn from_le_bytes(bytes: &[u8]) -> Result<Vector, VectorError> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(VectorError::InvalidByteLength(bytes.len()));
+        }
+        let data = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        Ok(Vector::from_vec(data))
+    }
+
+    /// Encode the vector as little-endian f32 bytes
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.data.iter().flat_map(|x| x.to_le_bytes()).collect()
+    }
+
+    /// Encode the little-endian f32 bytes as standard base64
+    #[cfg(feature = "base64")]
+    pub fn to_base64(&self) -> String {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(self.to_le_bytes())
+    }
+
+    /// Decode a vector from standard base64 of little-endian f32 bytes
+    #[cfg(feature = "base64")]
+    pub fn from_base64(encoded: &str) -> Result<Vector, VectorError> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|err| VectorError::InvalidEncoding(err.to_string()))?;
+        Vector::from_le_bytes(&bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Vector {
+    type Error = VectorError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Vector::from_le_bytes(bytes)
+    }
 }
 
 impl Index<usize> for Vector {
@@ -218,6 +358,41 @@ mod tests {
         assert!((normalized[1] - 0.8).abs() < 1e-6); // 8/10 = 0.8
     }
 
+    #[test]
+    fn test_vector_reductions() {
+        let v = Vector::from_slice(&[2.0, -5.0, 1.0, 6.0]);
+        assert_eq!(v.max_abs_component(), 6.0);
+        assert_eq!(v.min_component(), -5.0);
+        assert_eq!(v.max_component(), 6.0);
+        assert_eq!(v.sum(), 4.0);
+        assert_eq!(v.mean(), 1.0);
+
+        let negative = Vector::from_slice(&[-7.0, 3.0]);
+        assert_eq!(negative.max_abs_component(), 7.0);
+
+        let empty = Vector::new(0);
+        assert_eq!(empty.max_abs_component(), 0.0);
+        assert_eq!(empty.min_component(), 0.0);
+        assert_eq!(empty.max_component(), 0.0);
+        assert_eq!(empty.sum(), 0.0);
+        assert_eq!(empty.mean(), 0.0);
+    }
+
+    #[test]
+    fn test_vector_dot_product_masked() {
+        let v1 = Vector::from_slice(&[1.0, 2.0, 3.0]);
+        let v2 = Vector::from_slice(&[4.0, 5.0, 6.0]);
+
+        let full = v1.dot_product_masked(&v2, &[true, true, true]).unwrap();
+        assert_eq!(full, v1.dot_product(&v2).unwrap());
+
+        let partial = v1.dot_product_masked(&v2, &[true, false, true]).unwrap();
+        assert_eq!(partial, 22.0); // 1*4 + 3*6 = 4 + 18 = 22
+
+        assert!(v1.dot_product_masked(&v2, &[true, false]).is_err());
+        assert!(v1.dot_product_masked(&Vector::new(2), &[true, true, true]).is_err());
+    }
+
     #[test]
     fn test_vector_dimension_mismatch_errors() {
         let v1 = Vector::from_slice(&[1.0, 2.0]);
@@ -231,5 +406,85 @@ mod tests {
         
         let result = v1.subtract(&v2);
         assert!(result.is_err());
+
+        let result = Vector::centroid(&[v1, v2]);
+        assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_vector_projection_and_rejection() {
+        let v = Vector::from_slice(&[3.0, 3.0]);
+        let basis = Vector::from_slice(&[1.0, 0.0]);
+        assert_eq!(v.project_onto(&basis).unwrap().data(), &[3.0, 0.0]);
+        assert_eq!(v.reject_from(&basis).unwrap().data(), &[0.0, 3.0]);
+
+        // Projection does not depend on the length of the basis
+        let long_basis = Vector::from_slice(&[2.0, 2.0]);
+        assert_eq!(v.project_onto(&long_basis).unwrap().data(), &[3.0, 3.0]);
+
+        assert!(matches!(v.project_onto(&Vector::new(2)), Err(VectorError::ZeroVector)));
+        assert!(v.project_onto(&Vector::from_slice(&[1.0, 0.0, 0.0])).is_err());
+        assert!(v.reject_from(&Vector::from_slice(&[1.0])).is_err());
+    }
+
+    #[test]
+    fn test_vector_angle_between() {
+        let x = Vector::from_slice(&[1.0, 0.0]);
+        let y = Vector::from_slice(&[0.0, 2.0]);
+        assert!((x.angle_between_degrees(&y).unwrap() - 90.0).abs() < 1e-4);
+        assert!((x.angle_between(&y).unwrap() - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(x.angle_between_degrees(&x.scale(3.0)).unwrap(), 0.0);
+        assert!((x.angle_between_degrees(&x.scale(-1.0)).unwrap() - 180.0).abs() < 1e-4);
+
+        assert!(matches!(x.angle_between(&Vector::new(2)), Err(VectorError::ZeroVector)));
+        assert!(matches!(x.angle_between(&Vector::new(3)), Err(VectorError::DimensionsMismatch { .. })));
+    }
+
+    #[test]
+    fn test_vector_centroid() {
+        let vectors = [
+            Vector::from_slice(&[1.0, 2.0]),
+            Vector::from_slice(&[3.0, 6.0]),
+        ];
+        let centroid = Vector::centroid(&vectors).unwrap();
+        assert_eq!(centroid.data(), &[2.0, 4.0]);
+
+        assert!(matches!(Vector::centroid(&[]), Err(VectorError::EmptyInput)));
+    }
+
+    #[test]
+    fn test_vector_le_bytes_round_trip() {
+        let vector = Vector::from_slice(&[1.5, -2.25, 0.0, f32::MAX]);
+        let bytes = vector.to_le_bytes();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(&bytes[0..4], &1.5f32.to_le_bytes());
+
+        assert_eq!(Vector::from_le_bytes(&bytes).unwrap(), vector);
+        assert_eq!(Vector::try_from(bytes.as_slice()).unwrap(), vector);
+        assert_eq!(Vector::from_le_bytes(&[]).unwrap().size(), 0);
+    }
+
+    #[test]
+    fn test_vector_le_bytes_invalid_length() {
+        let err = Vector::from_le_bytes(&[0, 0, 128, 63, 0]).unwrap_err();
+        assert!(matches!(err, VectorError::InvalidByteLength(5)));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_vector_base64_round_trip() {
+        let vector = Vector::from_slice(&[1.0, -0.5, 3.25]);
+        let encoded = vector.to_base64();
+        assert_eq!(encoded, "AACAPwAAAL8AAFBA");
+        assert_eq!(Vector::from_base64(&encoded).unwrap(), vector);
+        assert_eq!(Vector::from_base64("").unwrap().size(), 0);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_vector_base64_malformed_input() {
+        assert!(matches!(Vector::from_base64("not base64!"), Err(VectorError::InvalidEncoding(_))));
+        // "AAAA" decodes to three bytes, which is not a whole f32
+        assert!(matches!(Vector::from_base64("AAAA"), Err(VectorError::InvalidByteLength(3))));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::core::Distance;
 use std::fmt;
 
 #[derive(Debug)]
@@ -6,6 +7,29 @@ pub enum VectorError {
     KeysAndVectorsMismatch,
     KeyNotFound,
     KeyAlreadyExists(String),
+    EmptyInput,
+    ZeroVector,
+    UnsupportedMetric(Distance),
+    InvalidByteLength(usize),
+    InvalidFlatLength { length: usize, dimensions: usize },
+    InvalidParameter(String),
+    InvalidEncoding(String),
+    NumericalOverflow(Distance),
+    ParseError { line: usize },
+    Io(std::io::Error),
+    NonFiniteComponent { index: usize },
+    InvariantViolation(String),
+    Context { op: &'static str, source: Box<VectorError> },
+}
+
+impl VectorError {
+    /// Get the innermost error beneath any `Context` wrappers
+    pub fn root_cause(&self) -> &VectorError {
+        match self {
+            VectorError::Context { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
 }
 
 impl fmt::Display for VectorError {
@@ -17,8 +41,56 @@ impl fmt::Display for VectorError {
             VectorError::KeysAndVectorsMismatch => write!(f, "Number of vectors must match number of keys"),
             VectorError::KeyNotFound => write!(f, "Key not found in the index"),
             VectorError::KeyAlreadyExists(ref key) => write!(f, "Key '{key}' already exists"),
+            VectorError::EmptyInput => write!(f, "At least one vector is required"),
+            VectorError::ZeroVector => write!(f, "Operation is undefined for a zero vector"),
+            VectorError::UnsupportedMetric(metric) => write!(f, "Distance metric '{metric}' is not supported by this index"),
+            VectorError::InvalidByteLength(length) => write!(f, "Byte length {length} is not a multiple of 4"),
+            VectorError::InvalidFlatLength { length, dimensions } => {
+                write!(f, "Flat buffer length {length} is not a multiple of dimension {dimensions}")
+            }
+            VectorError::InvalidParameter(ref message) => write!(f, "Invalid parameter: {message}"),
+            VectorError::InvalidEncoding(ref message) => write!(f, "Invalid encoding: {message}"),
+            VectorError::NumericalOverflow(metric) => write!(f, "Distance metric '{metric}' overflowed to a non-finite value"),
+            VectorError::ParseError { line } => write!(f, "Malformed input on line {line}"),
+            VectorError::Io(ref err) => write!(f, "I/O error: {err}"),
+            VectorError::NonFiniteComponent { index } => write!(f, "Component {index} is not a finite number"),
+            VectorError::InvariantViolation(ref message) => write!(f, "Store invariant violated: {message}"),
+            VectorError::Context { op, ref source } => write!(f, "{op}: {source}"),
         }
     }
 }
 
-impl std::error::Error for VectorError {} 
\ No newline at end of file
+/// Messages already include any wrapped error, so `source` stays `None` and chain reporters print
+/// each cause once. Use `root_cause` to reach the error beneath the `Context` wrappers.
+impl std::error::Error for VectorError {}
+
+/// Attach the name of the failing operation to a `VectorError`
+pub trait ResultExt<T> {
+    fn context(self, op: &'static str) -> Result<T, VectorError>;
+}
+
+impl<T> ResultExt<T> for Result<T, VectorError> {
+    fn context(self, op: &'static str) -> Result<T, VectorError> {
+        self.map_err(|source| VectorError::Context { op, source: Box::new(source) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_context_wrapping() {
+        let result: Result<(), VectorError> = Err(VectorError::KeyNotFound);
+        let err = result.context("inner").context("outer").unwrap_err();
+
+        assert_eq!(err.to_string(), "outer: inner: Key not found in the index");
+        assert!(matches!(err.root_cause(), VectorError::KeyNotFound));
+        assert!(std::error::Error::source(&err).is_none());
+
+        // A chain reporter walking source() prints the wrapped message exactly once
+        let io = Err::<(), _>(VectorError::Io(std::io::Error::other("disk full"))).context("load").unwrap_err();
+        assert_eq!(io.to_string(), "load: I/O error: disk full");
+        assert!(std::error::Error::source(&io).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::core::{Distance, OrderedF32, Vector};
+use crate::error::{ResultExt, VectorError};
+use std::collections::HashMap;
+
+/// Read-only snapshot of a `VectorDatabase` created by `VectorDatabase::freeze`.
+///
+/// Vectors are stored contiguously in key order with their norms precomputed, and search
+/// is an exact scan. There is no tombstone bookkeeping because the store cannot be mutated:
+///
+/// ```compile_fail
+/// use mini_vector_store_rs::{BackingStorage, Vector, VectorDatabase};
+///
+/// let mut frozen = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None).freeze();
+/// frozen.insert(Vector::from_slice(&[1.0, 2.0]), "key".to_string());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenVectorDatabase {
+    keys: Vec<String>,
+    data: Vec<f32>,
+    norms: Vec<f32>,
+    dimensions: usize,
+    distance_metric: Distance,
+    metadata_map: HashMap<String, String>,
+}
+
+impl FrozenVectorDatabase {
+    /// Lay out the vectors contiguously, sorted by key
+    pub(crate) fn new(
+        dimensions: usize,
+        distance_metric: Distance,
+        vectors: &HashMap<String, Vector>,
+        metadata_map: HashMap<String, String>,
+    ) -> Self {
+        let mut entries: Vec<(&String, &Vector)> = vectors.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut keys = Vec::with_capacity(entries.len());
+        let mut data = Vec::with_capacity(entries.len() * dimensions);
+        let mut norms = Vec::with_capacity(entries.len());
+        for (key, vector) in entries {
+            keys.push(key.clone());
+            data.extend_from_slice(vector.data());
+            norms.push(vector.norm());
+        }
+
+        Self {
+            keys,
+            data,
+            norms,
+            dimensions,
+            distance_metric,
+            metadata_map,
+        }
+    }
+
+    /// Perform exact similarity search, ties are broken by key
+    pub fn similarity_search(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
+        if query.size() != self.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() }).context("similarity_search");
+        }
+
+        if let Some(index) = query.data().iter().position(|x| !x.is_finite()) {
+            return Err(VectorError::NonFiniteComponent { index }).context("similarity_search");
+        }
+        
+        // Stored vectors with non-finite components are skipped, as the source database's indexes do
+        let query_norm = query.norm();
+        let mut results = Vec::with_capacity(self.keys.len());
+        for (index, norm) in self.norms.iter().enumerate() {
+            let distance = self.distance_metric
+                .candidate_distance_with_norms(self.vector_data(index), query.data(), *norm, query_norm)
+                .context("similarity_search")?;
+            if let Some(distance) = distance {
+                results.push((OrderedF32(distance), index));
+            }
+        }
+        results.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| self.keys[a.1].cmp(&self.keys[b.1])));
+        results.truncate(k);
+
+        Ok(results
+            .into_iter()
+            .map(|(distance, index)| (self.keys[index].clone(), distance.0))
+            .collect())
+    }
+
+    /// Components of the vector stored at `index`
+    fn vector_data(&self, index: usize) -> &[f32] {
+        &self.data[index * self.dimensions..(index + 1) * self.dimensions]
+    }
+
+    /// Get the components of a vector by key
+    pub fn get_vector(&self, key: &str) -> Option<&[f32]> {
+        self.keys
+            .binary_search_by(|probe| probe.as_str().cmp(key))
+            .ok()
+            .map(|index| self.vector_data(index))
+    }
+
+    /// Get metadata for a key
+    pub fn get_metadata(&self, key: &str) -> Option<&String> {
+        self.metadata_map.get(key)
+    }
+
+    /// Get the keys in storage order
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Get the number of vectors
+    pub fn size(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check if the store holds no vectors
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Get the dimensions of the store
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Get the distance metric used by the store
+    pub fn distance_metric(&self) -> Distance {
+        self.distance_metric
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BackingStorage, QueryPerformance, Vector, VectorDatabase};
+
+    #[test]
+    fn test_frozen_matches_source_database() {
+        let mut db = VectorDatabase::new(3, BackingStorage::KDTreeOnly, None);
+        for i in 0..60 {
+            let value = i as f32;
+            db.insert_with_metadata(
+                Vector::from_slice(&[(value * 0.7).sin(), (value * 1.3).cos(), value % 5.0]),
+                format!("key{i}"),
+                format!("meta{i}"),
+            ).unwrap();
+        }
+        db.remove("key7");
+
+        let queries = [
+            Vector::from_slice(&[0.0, 0.0, 0.0]),
+            Vector::from_slice(&[0.5, -0.5, 2.0]),
+            Vector::from_slice(&[-1.0, 1.0, 4.5]),
+        ];
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|query| db.similarity_search(query, 8, QueryPerformance::Accurate).unwrap())
+            .collect();
+
+        let frozen = db.freeze();
+        assert_eq!(frozen.size(), 59);
+        assert!(frozen.get_vector("key7").is_none());
+        assert_eq!(frozen.get_metadata("key3"), Some(&"meta3".to_string()));
+        for (query, expected) in queries.iter().zip(expected) {
+            assert_eq!(frozen.similarity_search(query, 8).unwrap(), expected);
+        }
+
+        assert!(frozen.similarity_search(&Vector::from_slice(&[1.0]), 1).is_err());
+    }
+
+    #[test]
+    fn test_frozen_skips_non_finite_vectors_like_source() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[1.0, 1.0]), "a".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[f32::NAN, 0.0]), "nan".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[1.0, 1.0]);
+        let expected = db.similarity_search(&query, 2, QueryPerformance::Accurate).unwrap();
+        assert_eq!(expected, [("a".to_string(), 0.0)]);
+        let frozen = db.freeze();
+        assert_eq!(frozen.similarity_search(&query, 2).unwrap(), expected);
+        assert!(frozen.similarity_search(&Vector::from_slice(&[f32::NAN, 1.0]), 1).is_err());
+    }
+}
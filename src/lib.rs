@@ -1,9 +1,20 @@
 pub mod core;
 pub mod error;
+pub mod frozen;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
-use crate::core::{Distance, KDTree, LSHIndex};
-use crate::error::VectorError;
-use std::collections::HashMap;
+use crate::core::{Distance, Index, KDTree, LSHIndex};
+use crate::error::{ResultExt, VectorError};
+use crate::frozen::FrozenVectorDatabase;
+#[cfg(feature = "metrics")]
+use crate::metrics::{StoreCounters, StoreStats};
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::BufRead;
 
 /// Search result with metadata
 #[derive(Debug, Clone)]
@@ -13,6 +24,49 @@ pub struct SearchResult {
     pub metadata: String,
 }
 
+/// Summary statistics of the stored vectors, useful when choosing a distance metric
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreAnalysis {
+    /// Population variance of each dimension across all vectors
+    pub dimension_variances: Vec<f32>,
+    /// Fraction of dimensions where every vector's component is near zero
+    pub near_zero_fraction: f32,
+    /// Mean L2 norm of the vectors
+    pub average_norm: f32,
+    /// True when every vector has a norm of approximately 1, false for an empty database
+    pub appears_normalized: bool,
+}
+
+/// Histogram of candidate distances, bins evenly split `[min, max]` and the last bin includes `max`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceHistogram {
+    /// Smallest candidate distance, 0.0 when there were no candidates
+    pub min: f32,
+    /// Largest candidate distance, 0.0 when there were no candidates
+    pub max: f32,
+    /// Number of candidates in each bin
+    pub counts: Vec<u32>,
+}
+
+/// Changes that turn one database snapshot into another, produced by `VectorDatabase::diff`.
+/// Only keys and vectors are compared, metadata is not part of the diff.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StoreDiff {
+    /// Keys missing from the base, with their vectors
+    pub added: Vec<(String, Vector)>,
+    /// Keys present in the base with a different vector, with the new vectors
+    pub updated: Vec<(String, Vector)>,
+    /// Keys present only in the base
+    pub removed: Vec<String>,
+}
+
+impl StoreDiff {
+    /// Check if the diff holds no changes
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// Backing storage options for the vector database
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BackingStorage {
@@ -25,7 +79,7 @@ pub enum BackingStorage {
 }
 
 /// Query performance preference
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum QueryPerformance {
     /// Prioritize speed over accuracy (uses LSH when available)
     Fast,
@@ -33,13 +87,49 @@ pub enum QueryPerformance {
     Accurate,
 }
 
+/// Extra checks run on every insert, for tracking down bugs at the cost of speed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationLevel {
+    /// Only the dimension check every insert already performs
+    #[default]
+    Off,
+    /// Also reject vectors with NaN or infinite components
+    Basic,
+    /// Also reject vectors whose norm overflows, and after each insert verify that the indexes
+    /// agree with each other and with the content hash, which costs a full scan
+    Paranoid,
+}
+
+/// Limits on a single index search: at most `k` hits within `radius`, after at most `max_visits`
+/// KD-tree nodes or LSH tables
+#[derive(Debug, Clone, Copy)]
+struct SearchBounds {
+    k: usize,
+    max_visits: Option<usize>,
+    radius: f32,
+}
+
+impl SearchBounds {
+    /// Plain k-nearest neighbors, optionally capped
+    fn nearest(k: usize, max_visits: Option<usize>) -> Self {
+        Self { k, max_visits, radius: f32::INFINITY }
+    }
+}
+
 /// Main vector database implementation
+#[derive(Clone)]
 pub struct VectorDatabase {
     kd_tree: Option<KDTree>,
     lsh_index: Option<LSHIndex>,
     backing_storage: BackingStorage,
     dimensions: usize,
+    distance_metric: Distance,
     metadata_map: HashMap<String, String>,
+    #[cfg(feature = "cache")]
+    content_hash: u64,
+    validation: ValidationLevel,
+    #[cfg(feature = "metrics")]
+    counters: StoreCounters,
 }
 
 impl VectorDatabase {
@@ -53,7 +143,7 @@ impl VectorDatabase {
         
         let kd_tree = match backing_storage {
             BackingStorage::KDTreeOnly | BackingStorage::Hybrid => {
-                Some(KDTree::new(dimensions, distance_metric))
+                Some(KDTree::new(dimensions, distance_metric).expect("KD-tree supports Euclidean distance"))
             }
             BackingStorage::LSHOnly => None,
         };
@@ -61,13 +151,23 @@ impl VectorDatabase {
         let lsh_index = match backing_storage {
             BackingStorage::LSHOnly | BackingStorage::Hybrid => {
                 let params = lsh_params.unwrap_or_default();
-                Some(LSHIndex::new(
-                    dimensions,
-                    params.num_tables,
-                    params.num_hash_functions,
-                    distance_metric,
-                    params.width,
-                ))
+                Some(match params.seed {
+                    Some(seed) => LSHIndex::with_seed(
+                        dimensions,
+                        params.num_tables,
+                        params.num_hash_functions,
+                        distance_metric,
+                        params.width,
+                        seed,
+                    ),
+                    None => LSHIndex::new(
+                        dimensions,
+                        params.num_tables,
+                        params.num_hash_functions,
+                        distance_metric,
+                        params.width,
+                    ),
+                }.expect("LSH supports Euclidean distance"))
             }
             BackingStorage::KDTreeOnly => None,
         };
@@ -77,15 +177,61 @@ impl VectorDatabase {
             lsh_index,
             backing_storage,
             dimensions,
+            distance_metric,
             metadata_map: HashMap::new(),
+            #[cfg(feature = "cache")]
+            content_hash: 0,
+            validation: ValidationLevel::Off,
+            #[cfg(feature = "metrics")]
+            counters: StoreCounters::default(),
         }
     }
     
+    /// Create a new vector database that runs the checks of `level` on every insert
+    pub fn with_validation(
+        dimensions: usize,
+        backing_storage: BackingStorage,
+        lsh_params: Option<LSHParams>,
+        level: ValidationLevel,
+    ) -> Self {
+        let mut db = Self::new(dimensions, backing_storage, lsh_params);
+        db.validation = level;
+        db
+    }
+    
+    /// Get the validation level applied to inserts
+    pub fn validation_level(&self) -> ValidationLevel {
+        self.validation
+    }
+    
     /// Insert a vector with a key
     pub fn insert(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
+        self.insert_entry(vector, key).context("insert")
+    }
+    
+    /// Validate and insert into every index, counting the insert, without error context
+    fn insert_entry(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
         if vector.size() != self.dimensions {
             return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: vector.size() });
         }
+        if self.validation != ValidationLevel::Off {
+            Self::validate_vector(&vector, self.validation)?;
+        }
+        
+        self.index_entry(vector, key)?;
+        #[cfg(feature = "metrics")]
+        self.counters.record_insert();
+        
+        if self.validation == ValidationLevel::Paranoid {
+            self.check_invariants()?;
+        }
+        Ok(())
+    }
+    
+    /// Insert into every index and update the content hash, for vectors already checked or being rewritten
+    fn index_entry(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
+        #[cfg(feature = "cache")]
+        let entry_hash = Self::entry_hash(&key, &vector);
         
         if let Some(ref mut kd_tree) = self.kd_tree {
             kd_tree.insert(vector.clone(), key.clone())?;
@@ -94,49 +240,148 @@ impl VectorDatabase {
         if let Some(ref mut lsh_index) = self.lsh_index {
             lsh_index.insert(vector, key)?;
         }
+        
+        #[cfg(feature = "cache")]
+        {
+            self.content_hash = self.content_hash.wrapping_add(entry_hash);
+        }
+        Ok(())
+    }
+    
+    /// Check a vector about to be inserted against the validation level
+    fn validate_vector(vector: &Vector, level: ValidationLevel) -> Result<(), VectorError> {
+        if let Some(index) = vector.data().iter().position(|x| !x.is_finite()) {
+            return Err(VectorError::NonFiniteComponent { index });
+        }
+        if level == ValidationLevel::Paranoid && !vector.norm().is_finite() {
+            return Err(VectorError::NumericalOverflow(Distance::Euclidean));
+        }
+        Ok(())
+    }
+    
+    /// Verify that both indexes hold the same vectors and that the content hash matches them
+    fn check_invariants(&self) -> Result<(), VectorError> {
+        if let (Some(kd_tree), Some(lsh_index)) = (&self.kd_tree, &self.lsh_index) {
+            if kd_tree.size() != lsh_index.size() {
+                return Err(VectorError::InvariantViolation(format!(
+                    "KD-tree holds {} vectors but LSH holds {}",
+                    kd_tree.size(),
+                    lsh_index.size()
+                )));
+            }
+            let mismatched = kd_tree.get_all_vectors()
+                .iter()
+                .find(|(key, vector)| lsh_index.get_vector(key) != Some(*vector));
+            if let Some((key, _)) = mismatched {
+                return Err(VectorError::InvariantViolation(format!("indexes disagree on the vector for '{key}'")));
+            }
+        }
+        
+        #[cfg(feature = "cache")]
+        if self.recompute_content_hash() != self.content_hash {
+            return Err(VectorError::InvariantViolation("content hash does not match the stored vectors".to_string()));
+        }
         Ok(())
     }
     
     /// Insert a vector with a key and metadata
     pub fn insert_with_metadata(&mut self, vector: Vector, key: String, metadata: String) -> Result<(), VectorError> {
-        self.metadata_map.insert(key.clone(), metadata);
-        self.insert(vector, key)
+        self.insert_entry(vector, key.clone()).context("insert_with_metadata")?;
+        self.metadata_map.insert(key, metadata);
+        Ok(())
+    }
+    
+    /// Insert a vector scaled to unit length, a zero vector is rejected.
+    /// The database ranks by Euclidean distance, which on unit vectors orders
+    /// results the same as cosine distance since |a - b|^2 = 2 - 2 cos(a, b).
+    pub fn insert_normalized(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
+        if vector.norm() == 0.0 {
+            return Err(VectorError::ZeroVector).context("insert_normalized");
+        }
+        self.insert_entry(vector.normalized(), key).context("insert_normalized")
     }
     
     /// Insert multiple vectors in batch
     pub fn batch_insert(&mut self, vectors: Vec<Vector>, keys: Vec<String>) -> Result<(), VectorError> {
         if vectors.len() != keys.len() {
-            return Err(VectorError::KeysAndVectorsMismatch);
+            return Err(VectorError::KeysAndVectorsMismatch).context("batch_insert");
         }
         
         for (vector, key) in vectors.into_iter().zip(keys) {
-            self.insert(vector, key)?;
+            self.insert_entry(vector, key).context("batch_insert")?;
         }
         Ok(())
     }
     
     /// Perform similarity search
     pub fn similarity_search(&self, query: &Vector, k: usize, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
-        if query.size() != self.dimensions {
-            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() });
+        self.search_index(query.data(), k, &HashSet::new(), None, performance).context("similarity_search")
+    }
+    
+    /// Perform similarity search into a caller-provided buffer, which is cleared first and keeps its capacity.
+    /// The index writes its hits straight into the buffer, so one with capacity for k results is never reallocated.
+    pub fn search_into(&self, query: &Vector, k: usize, out: &mut Vec<(String, f32)>, performance: QueryPerformance) -> Result<(), VectorError> {
+        self.search_index_into(query.data(), &HashSet::new(), SearchBounds::nearest(k, None), out, performance).context("search_into")
+    }
+    
+    /// Perform similarity search with a query given as a slice of components
+    pub fn search_slice(&self, query: &[f32], k: usize, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        self.search_index(query, k, &HashSet::new(), None, performance).context("search_slice")
+    }
+    
+    /// Perform similarity search that skips the excluded keys while still returning up to k results
+    pub fn search_excluding(&self, query: &Vector, k: usize, exclude: &HashSet<String>, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        self.search_index(query.data(), k, exclude, None, performance).context("search_excluding")
+    }
+    
+    /// Dispatch a search to the index chosen by the backing storage and performance preference
+    fn search_index(
+        &self,
+        query: &[f32],
+        k: usize,
+        exclude: &HashSet<String>,
+        max_visits: Option<usize>,
+        performance: QueryPerformance,
+    ) -> Result<Vec<(String, f32)>, VectorError> {
+        let mut results = Vec::new();
+        self.search_index_into(query, exclude, SearchBounds::nearest(k, max_visits), &mut results, performance)?;
+        Ok(results)
+    }
+    
+    /// Dispatch a search like `search_index`, with the index writing its hits straight into `out`
+    fn search_index_into(
+        &self,
+        query: &[f32],
+        exclude: &HashSet<String>,
+        bounds: SearchBounds,
+        out: &mut Vec<(String, f32)>,
+        performance: QueryPerformance,
+    ) -> Result<(), VectorError> {
+        out.clear();
+        if query.len() != self.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.len() });
         }
         
-        match (self.backing_storage, performance) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("similarity_search", k = bounds.k, ?performance).entered();
+        
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+        let candidates_scanned = match (self.backing_storage, performance) {
             // KD-tree only scenarios
             (BackingStorage::KDTreeOnly, _) => {
                 if let Some(ref kd_tree) = self.kd_tree {
-                    kd_tree.nearest_neighbors(query, k)
+                    kd_tree.nearest_neighbors_counted(query, bounds.k, exclude, bounds.max_visits, bounds.radius, out)
                 } else {
-                    Ok(Vec::new())
+                    Ok(0)
                 }
             }
             
             // LSH only scenarios
             (BackingStorage::LSHOnly, _) => {
                 if let Some(ref lsh_index) = self.lsh_index {
-                    lsh_index.nearest_neighbors(query, k)
+                    lsh_index.nearest_neighbors_counted(query, bounds.k, exclude, bounds.max_visits, bounds.radius, out)
                 } else {
-                    Ok(Vec::new())
+                    Ok(0)
                 }
             }
             
@@ -144,26 +389,208 @@ impl VectorDatabase {
             (BackingStorage::Hybrid, QueryPerformance::Fast) => {
                 // Use LSH for speed
                 if let Some(ref lsh_index) = self.lsh_index {
-                    lsh_index.nearest_neighbors(query, k)
+                    lsh_index.nearest_neighbors_counted(query, bounds.k, exclude, bounds.max_visits, bounds.radius, out)
                 } else {
-                    Ok(Vec::new())
+                    Ok(0)
                 }
             }
             
             (BackingStorage::Hybrid, QueryPerformance::Accurate) => {
                 // Use KD-tree for accuracy
                 if let Some(ref kd_tree) = self.kd_tree {
-                    kd_tree.nearest_neighbors(query, k)
+                    kd_tree.nearest_neighbors_counted(query, bounds.k, exclude, bounds.max_visits, bounds.radius, out)
                 } else {
-                    Ok(Vec::new())
+                    Ok(0)
+                }
+            }
+        }?;
+        
+        #[cfg(feature = "metrics")]
+        self.counters.record_search(candidates_scanned);
+        Ok(())
+    }
+    
+    /// Perform similarity search visiting at most `max_visits` KD-tree nodes or LSH tables,
+    /// returning the best results found within that budget. None searches without a cap.
+    pub fn similarity_search_capped(&self, query: &Vector, k: usize, max_visits: Option<usize>, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        self.search_index(query.data(), k, &HashSet::new(), max_visits, performance).context("similarity_search_capped")
+    }
+    
+    /// Find the neighbors of a stored vector by its key, never including the key itself
+    pub fn search_by_id(&self, key: &str, k: usize, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        let query = self.get_vector(key).ok_or(VectorError::KeyNotFound).context("search_by_id")?;
+        let exclude = HashSet::from([key.to_string()]);
+        self.search_index(query.data(), k, &exclude, None, performance).context("search_by_id")
+    }
+    
+    /// Perform similarity search with the centroid of several query vectors
+    pub fn search_multi(&self, queries: &[Vector], k: usize, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        if let Some(query) = queries.iter().find(|query| query.size() != self.dimensions) {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() }).context("search_multi");
+        }
+
+        let centroid = Vector::centroid(queries).context("search_multi")?;
+        self.search_index(centroid.data(), k, &HashSet::new(), None, performance).context("search_multi")
+    }
+    
+    /// Perform similarity search with distances rounded to `decimals` places, ranking uses full precision
+    pub fn search_rounded(&self, query: &Vector, k: usize, decimals: u32, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        let factor = 10f64.powi(decimals as i32);
+        let results = self.search_index(query.data(), k, &HashSet::new(), None, performance).context("search_rounded")?;
+        
+        Ok(results
+            .into_iter()
+            .map(|(key, distance)| (key, ((distance as f64 * factor).round() / factor) as f32))
+            .collect())
+    }
+    
+    /// Perform similarity search returning relevance scores in [0, 1] where 1 is best,
+    /// see `Distance::similarity_score` for the mapping used by each metric
+    pub fn search_scored(&self, query: &Vector, k: usize, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        let results = self.search_index(query.data(), k, &HashSet::new(), None, performance).context("search_scored")?;
+        
+        Ok(results
+            .into_iter()
+            .map(|(key, distance)| (key, self.distance_metric.similarity_score(distance)))
+            .collect())
+    }
+    
+    /// Perform similarity search returning Gaussian kernel similarities `exp(-gamma * d^2)`,
+    /// ranked by descending kernel value. The database always ranks by Euclidean distance, as the kernel needs.
+    pub fn rbf_search(&self, query: &Vector, k: usize, gamma: f32, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        Distance::rbf_kernel(0.0, gamma).context("rbf_search")?;
+        let results = self.search_index(query.data(), k, &HashSet::new(), None, performance).context("rbf_search")?;
+        
+        results
+            .into_iter()
+            .map(|(key, distance)| Ok((key, Distance::rbf_kernel(distance, gamma).context("rbf_search")?)))
+            .collect()
+    }
+    
+    /// Perform exact search with f64 distance accumulation, `total_cmp` ordering and ties broken by key,
+    /// so the same inputs give the same ordering on every platform
+    pub fn search_stable(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
+        if query.size() != self.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() }).context("search_stable");
+        }
+        
+        let mut results = Vec::with_capacity(self.live_count());
+        for (key, vector) in self.get_all_vectors() {
+            results.push((self.distance_metric.distance_f64(query, vector).context("search_stable")?, key));
+        }
+        results.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        results.truncate(k);
+        
+        Ok(results
+            .into_iter()
+            .map(|(distance, key)| (key.clone(), distance as f32))
+            .collect())
+    }
+    
+    /// Perform similarity search also returning a `bins`-long histogram of every candidate's distance,
+    /// with bins spanning the smallest to the largest candidate distance
+    pub fn search_with_histogram(&self, query: &Vector, k: usize, bins: usize, performance: QueryPerformance) -> Result<(Vec<(String, f32)>, DistanceHistogram), VectorError> {
+        if bins == 0 {
+            return Err(VectorError::InvalidParameter("histogram needs at least one bin".to_string())).context("search_with_histogram");
+        }
+        let mut results = self.search_index(query.data(), self.live_count(), &HashSet::new(), None, performance).context("search_with_histogram")?;
+        
+        let min = results.first().map_or(0.0, |(_, distance)| *distance);
+        let max = results.last().map_or(0.0, |(_, distance)| *distance);
+        let range = max - min;
+        let mut counts = vec![0u32; bins];
+        for (_, distance) in &results {
+            let bin = if range > 0.0 { ((distance - min) / range * bins as f32) as usize } else { 0 };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        let histogram = DistanceHistogram { min, max, counts };
+        
+        results.truncate(k);
+        Ok((results, histogram))
+    }
+    
+    /// Find every vector within `radius` of the query, nearest first with ties broken by key,
+    /// keeping at most `max_results` when set
+    pub fn search_range(&self, query: &Vector, radius: f32, max_results: Option<usize>, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        // The radius bounds the KD-tree search from the start, so only the ball around the query is explored
+        let bounds = SearchBounds { k: max_results.unwrap_or(usize::MAX), max_visits: None, radius };
+        let mut results = Vec::new();
+        self.search_index_into(query.data(), &HashSet::new(), bounds, &mut results, performance).context("search_range")?;
+        Ok(results)
+    }
+    
+    /// Summarize the k nearest neighbors as their softmax-weighted average, with weights `exp(-d / temperature)`
+    /// so a low temperature approaches the nearest neighbor and a high one the plain centroid
+    pub fn pooled_neighbor(&self, query: &Vector, k: usize, temperature: f32, performance: QueryPerformance) -> Result<Vector, VectorError> {
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(VectorError::InvalidParameter(format!("temperature must be positive and finite, got {temperature}"))).context("pooled_neighbor");
+        }
+        let results = self.search_index(query.data(), k, &HashSet::new(), None, performance).context("pooled_neighbor")?;
+        let nearest = results.first().map(|(_, distance)| *distance).ok_or(VectorError::EmptyInput).context("pooled_neighbor")?;
+        
+        // Shifting by the nearest distance keeps the largest weight at 1 so the exponentials cannot underflow to 0
+        let mut pooled = Vector::new(self.dimensions);
+        let mut total_weight = 0.0;
+        for (key, distance) in &results {
+            let vector = self.get_vector(key).ok_or(VectorError::KeyNotFound).context("pooled_neighbor")?;
+            let weight = (-(distance - nearest) / temperature).exp();
+            pooled = pooled.add(&vector.scale(weight)).context("pooled_neighbor")?;
+            total_weight += weight;
+        }
+        Ok(pooled.scale(1.0 / total_weight))
+    }
+    
+    /// Re-rank candidate keys, such as the output of an approximate search, by exact distance to the query
+    pub fn rerank(&self, query: &Vector, candidates: &[String], k: usize) -> Result<Vec<(String, f32)>, VectorError> {
+        if query.size() != self.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: self.dimensions, found: query.size() }).context("rerank");
+        }
+        if let Some(index) = query.data().iter().position(|x| !x.is_finite()) {
+            return Err(VectorError::NonFiniteComponent { index }).context("rerank");
+        }
+        
+        // Candidates with non-finite components cannot be ranked and are dropped, as in the index searches
+        let mut results = Vec::with_capacity(candidates.len());
+        for key in candidates {
+            let vector = self.get_vector(key).ok_or(VectorError::KeyNotFound).context("rerank")?;
+            if let Some(distance) = self.distance_metric.candidate_distance(vector.data(), query.data()).context("rerank")? {
+                results.push((key.clone(), distance));
+            }
+        }
+        results.sort_by_key(|(_, distance)| OrderedF32(*distance));
+        results.truncate(k);
+        Ok(results)
+    }
+    
+    /// Perform similarity search that greedily keeps a result only if it lies at least
+    /// `min_separation` away from every result already selected, so near-duplicates collapse to one
+    pub fn search_diverse(&self, query: &Vector, k: usize, min_separation: f32, performance: QueryPerformance) -> Result<Vec<(String, f32)>, VectorError> {
+        let candidates = self.search_index(query.data(), self.live_count(), &HashSet::new(), None, performance).context("search_diverse")?;
+        
+        let mut selected: Vec<(String, f32)> = Vec::with_capacity(k);
+        for (key, distance) in candidates {
+            if selected.len() >= k {
+                break;
+            }
+            let vector = self.get_vector(&key).ok_or(VectorError::KeyNotFound).context("search_diverse")?;
+            let mut separated = true;
+            for (selected_key, _) in &selected {
+                let selected_vector = self.get_vector(selected_key).ok_or(VectorError::KeyNotFound).context("search_diverse")?;
+                if self.distance_metric.distance(vector, selected_vector).context("search_diverse")? < min_separation {
+                    separated = false;
+                    break;
                 }
             }
+            if separated {
+                selected.push((key, distance));
+            }
         }
+        Ok(selected)
     }
     
     /// Perform similarity search with metadata
     pub fn similarity_search_with_metadata(&self, query: &Vector, k: usize, performance: QueryPerformance) -> Result<Vec<SearchResult>, VectorError> {
-        let results = self.similarity_search(query, k, performance)?;
+        let results = self.search_index(query.data(), k, &HashSet::new(), None, performance).context("similarity_search_with_metadata")?;
         
         Ok(results
             .into_iter()
@@ -178,11 +605,37 @@ impl VectorDatabase {
             .collect())
     }
     
+    /// Perform similarity search keeping at most `k_per_group` nearest results for each group,
+    /// where `group_of` maps a result's metadata (empty when unset) to its group
+    pub fn search_grouped<G, F>(&self, query: &Vector, k_per_group: usize, group_of: F, performance: QueryPerformance) -> Result<Vec<SearchResult>, VectorError>
+    where
+        G: Eq + Hash,
+        F: Fn(&str) -> G,
+    {
+        let candidates = self.search_index(query.data(), self.live_count(), &HashSet::new(), None, performance).context("search_grouped")?;
+        
+        let mut group_counts: HashMap<G, usize> = HashMap::new();
+        let mut results = Vec::new();
+        for (key, distance) in candidates {
+            let metadata = self.metadata_map.get(&key).cloned().unwrap_or_default();
+            let count = group_counts.entry(group_of(&metadata)).or_insert(0);
+            if *count < k_per_group {
+                *count += 1;
+                results.push(SearchResult {
+                    key,
+                    distance,
+                    metadata,
+                });
+            }
+        }
+        Ok(results)
+    }
+    
     /// Perform batch similarity search
     pub fn batch_similarity_search(&self, queries: Vec<Vector>, k: usize, performance: QueryPerformance) -> Result<Vec<Vec<(String, f32)>>, VectorError> {
         queries
             .iter()
-            .map(|query| self.similarity_search(query, k, performance))
+            .map(|query| self.search_index(query.data(), k, &HashSet::new(), None, performance).context("batch_similarity_search"))
             .collect()
     }
     
@@ -193,10 +646,9 @@ impl VectorDatabase {
     
     /// Get a vector by key
     pub fn get_vector(&self, key: &str) -> Option<&Vector> {
-        if let Some(ref kd_tree) = self.kd_tree {
-            if let Some(vector) = kd_tree.get_vector(key) {
-                return Some(vector);
-            }
+        if let Some(ref kd_tree) = self.kd_tree
+            && let Some(vector) = kd_tree.get_vector(key) {
+            return Some(vector);
         }
         
         if let Some(ref lsh_index) = self.lsh_index {
@@ -211,6 +663,11 @@ impl VectorDatabase {
         self.dimensions
     }
     
+    /// Get the distance metric used for search
+    pub fn distance_metric(&self) -> Distance {
+        self.distance_metric
+    }
+    
     /// Get the current backing storage
     pub fn backing_storage(&self) -> BackingStorage {
         self.backing_storage
@@ -230,8 +687,18 @@ impl VectorDatabase {
         EMPTY.get_or_init(HashMap::new)
     }
     
-    /// Get the number of vectors in the database
+    /// Get the number of vectors in the database, same as `live_count`
     pub fn size(&self) -> usize {
+        self.live_count()
+    }
+
+    /// Check if the database is empty
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// Get the number of live (searchable) vectors
+    pub fn live_count(&self) -> usize {
         if let Some(ref kd_tree) = self.kd_tree {
             kd_tree.size()
         } else if let Some(ref lsh_index) = self.lsh_index {
@@ -240,14 +707,333 @@ impl VectorDatabase {
             0
         }
     }
+
+    /// Get the number of occupied slots, including tombstoned vectors not yet rebuilt away
+    pub fn total_slots(&self) -> usize {
+        if let Some(ref kd_tree) = self.kd_tree {
+            kd_tree.size() + kd_tree.tombstone_count()
+        } else {
+            self.live_count()
+        }
+    }
     
-    /// Check if the database is empty
-    pub fn is_empty(&self) -> bool {
-        self.size() == 0
+    /// Normalize every stored vector to unit length and rebuild the indexes around the new values.
+    /// Vectors with a zero or non-finite norm cannot be normalized and are left unchanged, returns how many were skipped.
+    /// Rewriting a vector does not count as an insert in the metrics counters.
+    pub fn normalize_all(&mut self) -> Result<usize, VectorError> {
+        let mut entries: Vec<(String, Vector)> = self.get_all_vectors()
+            .iter()
+            .map(|(key, vector)| (key.clone(), vector.clone()))
+            .collect();
+        
+        let mut skipped = 0;
+        for (_, vector) in &mut entries {
+            let norm = vector.norm();
+            if norm == 0.0 || !norm.is_finite() {
+                skipped += 1;
+            } else {
+                vector.normalize();
+            }
+        }
+        
+        if let Some(ref mut kd_tree) = self.kd_tree {
+            kd_tree.clear();
+        }
+        
+        if let Some(ref mut lsh_index) = self.lsh_index {
+            lsh_index.clear();
+        }
+        
+        #[cfg(feature = "cache")]
+        {
+            self.content_hash = 0;
+        }
+        for (key, vector) in entries {
+            self.index_entry(vector, key).context("normalize_all")?;
+        }
+        
+        // Rebuild once so the KD-tree is balanced rather than shaped by hash map order
+        self.compact();
+        if self.validation == ValidationLevel::Paranoid {
+            self.check_invariants().context("normalize_all")?;
+        }
+        Ok(skipped)
+    }
+    
+    /// Report per-dimension variance, near-zero dimensions and norm statistics of the stored vectors
+    pub fn analyze(&self) -> StoreAnalysis {
+        const NEAR_ZERO: f32 = 1e-6;
+        const NORM_TOLERANCE: f32 = 1e-3;
+        
+        let vectors = self.get_all_vectors();
+        let count = vectors.len() as f32;
+        let mut means = vec![0.0f32; self.dimensions];
+        let mut max_abs = vec![0.0f32; self.dimensions];
+        let mut norm_sum = 0.0;
+        let mut appears_normalized = !vectors.is_empty();
+        
+        for vector in vectors.values() {
+            for (i, &x) in vector.data().iter().enumerate() {
+                means[i] += x / count;
+                max_abs[i] = max_abs[i].max(x.abs());
+            }
+            let norm = vector.norm();
+            norm_sum += norm;
+            appears_normalized &= (norm - 1.0).abs() <= NORM_TOLERANCE;
+        }
+        
+        let mut dimension_variances = vec![0.0f32; self.dimensions];
+        for vector in vectors.values() {
+            for (i, &x) in vector.data().iter().enumerate() {
+                dimension_variances[i] += (x - means[i]).powi(2) / count;
+            }
+        }
+        
+        let near_zero_dimensions = max_abs.iter().filter(|&&x| x <= NEAR_ZERO).count();
+        StoreAnalysis {
+            dimension_variances,
+            near_zero_fraction: if self.dimensions == 0 { 0.0 } else { near_zero_dimensions as f32 / self.dimensions as f32 },
+            average_norm: if vectors.is_empty() { 0.0 } else { norm_sum / count },
+            appears_normalized,
+        }
+    }
+    
+    /// Load word vectors in GloVe/word2vec text format, one `word f1 ... fn` per line, with an optional
+    /// `count dim` header line. Returns the database keyed by word plus the words in file order.
+    pub fn from_word_vectors<R: BufRead>(
+        reader: R,
+        backing_storage: BackingStorage,
+        lsh_params: Option<LSHParams>,
+    ) -> Result<(Self, Vec<String>), VectorError> {
+        let mut db: Option<Self> = None;
+        let mut words = Vec::new();
+        
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.map_err(VectorError::Io).context("from_word_vectors")?;
+            let line_number = index + 1;
+            let mut tokens = line.split_whitespace();
+            let Some(word) = tokens.next() else {
+                continue;
+            };
+            let values: Vec<&str> = tokens.collect();
+            
+            if index == 0 && values.len() == 1 && word.parse::<usize>().is_ok() && values[0].parse::<usize>().is_ok() {
+                continue;
+            }
+            
+            let components = values
+                .iter()
+                .map(|value| value.parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| VectorError::ParseError { line: line_number })
+                .context("from_word_vectors")?;
+            if components.is_empty() {
+                return Err(VectorError::ParseError { line: line_number }).context("from_word_vectors");
+            }
+            
+            let db = db.get_or_insert_with(|| Self::new(components.len(), backing_storage, lsh_params.clone()));
+            if components.len() != db.dimensions {
+                return Err(VectorError::ParseError { line: line_number }).context("from_word_vectors");
+            }
+            db.insert_entry(Vector::from_vec(components), word.to_string()).context("from_word_vectors")?;
+            words.push(word.to_string());
+        }
+        
+        let db = db.ok_or(VectorError::EmptyInput).context("from_word_vectors")?;
+        Ok((db, words))
+    }
+    
+    /// Export all vectors as one contiguous buffer in key order, returning the keys, the buffer and the dimension
+    pub fn to_flat(&self) -> (Vec<String>, Vec<f32>, usize) {
+        let vectors = self.get_all_vectors();
+        let mut keys: Vec<String> = vectors.keys().cloned().collect();
+        keys.sort();
+        
+        let mut data = Vec::with_capacity(keys.len() * self.dimensions);
+        for key in &keys {
+            data.extend_from_slice(vectors[key].data());
+        }
+        (keys, data, self.dimensions)
+    }
+    
+    /// Build a database from a contiguous buffer holding one `dimensions`-long vector per key.
+    /// A zero dimension is rejected as an invalid flat length.
+    pub fn from_flat(
+        keys: Vec<String>,
+        data: Vec<f32>,
+        dimensions: usize,
+        backing_storage: BackingStorage,
+        lsh_params: Option<LSHParams>,
+    ) -> Result<Self, VectorError> {
+        if dimensions == 0 || !data.len().is_multiple_of(dimensions) {
+            return Err(VectorError::InvalidFlatLength { length: data.len(), dimensions }).context("from_flat");
+        }
+        if keys.len() * dimensions != data.len() {
+            return Err(VectorError::KeysAndVectorsMismatch).context("from_flat");
+        }
+        
+        let mut db = Self::new(dimensions, backing_storage, lsh_params);
+        for (index, key) in keys.into_iter().enumerate() {
+            let vector = Vector::from_slice(&data[index * dimensions..(index + 1) * dimensions]);
+            db.insert_entry(vector, key).context("from_flat")?;
+        }
+        Ok(db)
+    }
+    
+    /// Compute the changes that turn `base` into this database, sorted by key.
+    /// Both databases must share dimension, every database uses Euclidean distance.
+    pub fn diff(&self, base: &VectorDatabase) -> Result<StoreDiff, VectorError> {
+        if self.dimensions != base.dimensions {
+            return Err(VectorError::DimensionsMismatch { expected: base.dimensions, found: self.dimensions }).context("diff");
+        }
+        
+        let current = self.get_all_vectors();
+        let previous = base.get_all_vectors();
+        let mut diff = StoreDiff::default();
+        for (key, vector) in current {
+            match previous.get(key) {
+                None => diff.added.push((key.clone(), vector.clone())),
+                Some(old) if old != vector => diff.updated.push((key.clone(), vector.clone())),
+                Some(_) => {}
+            }
+        }
+        diff.removed = previous
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+        
+        diff.added.sort_by(|a, b| a.0.cmp(&b.0));
+        diff.updated.sort_by(|a, b| a.0.cmp(&b.0));
+        diff.removed.sort();
+        Ok(diff)
+    }
+    
+    /// Bring this replica up to date with a diff, updated keys keep their metadata while removed keys lose it.
+    /// Changes made before a failing entry are kept.
+    pub fn apply_diff(&mut self, diff: &StoreDiff) -> Result<(), VectorError> {
+        for key in &diff.removed {
+            self.remove(key);
+        }
+        
+        for (key, _) in &diff.updated {
+            #[cfg(feature = "cache")]
+            if let Some(old) = self.get_vector(key) {
+                let entry_hash = Self::entry_hash(key, old);
+                self.content_hash = self.content_hash.wrapping_sub(entry_hash);
+            }
+            if let Some(ref mut kd_tree) = self.kd_tree {
+                kd_tree.remove(key);
+            }
+            if let Some(ref mut lsh_index) = self.lsh_index {
+                lsh_index.remove(key);
+            }
+        }
+        
+        // Reinserting onto a tombstone rebuilds the KD-tree, so drop them all in one rebuild first
+        if !diff.updated.is_empty() {
+            self.compact();
+        }
+        
+        for (key, vector) in diff.updated.iter().chain(&diff.added) {
+            self.insert_entry(vector.clone(), key.clone()).context("apply_diff")?;
+        }
+        Ok(())
+    }
+    
+    /// Convert into a read-only store with contiguous storage and precomputed norms
+    pub fn freeze(mut self) -> FrozenVectorDatabase {
+        let metadata_map = std::mem::take(&mut self.metadata_map);
+        FrozenVectorDatabase::new(self.dimensions, self.distance_metric, self.get_all_vectors(), metadata_map)
+    }
+    
+    /// Get the search and insert counters accumulated since creation or the last `reset_stats`
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> StoreStats {
+        self.counters.snapshot()
+    }
+    
+    /// Reset the search and insert counters to zero
+    #[cfg(feature = "metrics")]
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+    
+    /// Drop tombstoned KD-tree nodes and empty LSH buckets
+    pub fn compact(&mut self) {
+        if let Some(ref mut kd_tree) = self.kd_tree {
+            kd_tree.compact();
+        }
+        
+        if let Some(ref mut lsh_index) = self.lsh_index {
+            lsh_index.compact();
+        }
+    }
+    
+    /// Release spare capacity held by the indexes and metadata, most effective after `compact`
+    pub fn shrink_to_fit(&mut self) {
+        if let Some(ref mut kd_tree) = self.kd_tree {
+            kd_tree.shrink_to_fit();
+        }
+        
+        if let Some(ref mut lsh_index) = self.lsh_index {
+            lsh_index.shrink_to_fit();
+        }
+        
+        self.metadata_map.shrink_to_fit();
+    }
+    
+    /// Estimate the heap memory used by vector data, metadata and index structures in bytes.
+    /// This is an estimate from container sizes and capacities, not a measurement of the allocator.
+    pub fn memory_usage(&self) -> usize {
+        let kd_tree_bytes = self.kd_tree.as_ref().map_or(0, KDTree::memory_usage);
+        let lsh_bytes = self.lsh_index.as_ref().map_or(0, LSHIndex::memory_usage);
+        let metadata_bytes = self.metadata_map.capacity() * std::mem::size_of::<(String, String)>()
+            + self.metadata_map
+                .iter()
+                .map(|(key, metadata)| key.capacity() + metadata.capacity())
+                .sum::<usize>();
+        
+        kd_tree_bytes + lsh_bytes + metadata_bytes
+    }
+    
+    /// Get an order-independent hash of all stored keys and vectors, changes whenever the contents do.
+    /// The `cache` feature keeps it up to date on every change, so reading it is O(1).
+    #[cfg(feature = "cache")]
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+    
+    /// Get an order-independent hash of all stored keys and vectors, changes whenever the contents do.
+    /// Without the `cache` feature it is computed from every entry on each call.
+    #[cfg(not(feature = "cache"))]
+    pub fn content_hash(&self) -> u64 {
+        self.recompute_content_hash()
+    }
+    
+    /// Fold the hashes of all live entries
+    fn recompute_content_hash(&self) -> u64 {
+        self.get_all_vectors()
+            .iter()
+            .fold(0u64, |hash, (key, vector)| hash.wrapping_add(Self::entry_hash(key, vector)))
+    }
+    
+    /// Hash a single entry, combined into `content_hash` by wrapping addition
+    fn entry_hash(key: &str, vector: &Vector) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        vector.hash(&mut hasher);
+        hasher.finish()
     }
     
     /// Remove a vector by key
     pub fn remove(&mut self, key: &str) {
+        #[cfg(feature = "cache")]
+        if let Some(vector) = self.get_vector(key) {
+            let entry_hash = Self::entry_hash(key, vector);
+            self.content_hash = self.content_hash.wrapping_sub(entry_hash);
+        }
+        
         if let Some(ref mut kd_tree) = self.kd_tree {
             kd_tree.remove(key);
         }
@@ -260,12 +1046,28 @@ impl VectorDatabase {
     }
 }
 
+impl Index for VectorDatabase {
+    fn insert(&mut self, vector: Vector, key: String) -> Result<(), VectorError> {
+        VectorDatabase::insert(self, vector, key)
+    }
+    
+    fn search(&self, query: &Vector, k: usize) -> Result<Vec<(String, f32)>, VectorError> {
+        self.similarity_search(query, k, QueryPerformance::Accurate)
+    }
+    
+    fn len(&self) -> usize {
+        self.live_count()
+    }
+}
+
 /// Parameters for LSH index configuration
 #[derive(Debug, Clone)]
 pub struct LSHParams {
     pub num_tables: usize,
     pub num_hash_functions: usize,
     pub width: f32,
+    /// Seed for the hash functions, None seeds from system entropy
+    pub seed: Option<u64>,
 }
 
 impl Default for LSHParams {
@@ -274,12 +1076,13 @@ impl Default for LSHParams {
             num_tables: 10,
             num_hash_functions: 5,
             width: 4.0,
+            seed: None,
         }
     }
 }
 
 // Re-export core types for public use
-pub use crate::core::Vector;
+pub use crate::core::{OrderedF32, Vector};
 
 #[cfg(test)]
 mod tests {
@@ -301,6 +1104,23 @@ mod tests {
         assert_eq!(db.get_metadata("test"), Some(&"metadata".to_string()));
     }
 
+    #[test]
+    fn test_vector_database_caller_assigned_keys() {
+        let mut db = VectorDatabase::new(2, BackingStorage::Hybrid, None);
+        db.insert_with_metadata(Vector::from_slice(&[1.0, 2.0]), "pk-42".to_string(), "first".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[3.0, 4.0]), "pk-7".to_string()).unwrap();
+
+        let result = db.insert_with_metadata(Vector::from_slice(&[5.0, 6.0]), "pk-42".to_string(), "second".to_string());
+        assert!(matches!(result.unwrap_err().root_cause(), VectorError::KeyAlreadyExists(key) if key == "pk-42"));
+        assert_eq!(db.get_metadata("pk-42"), Some(&"first".to_string()));
+        assert_eq!(db.get_vector("pk-42").unwrap().data(), &[1.0, 2.0]);
+
+        let query = Vector::from_slice(&[3.0, 4.0]);
+        let results = db.similarity_search(&query, 2, QueryPerformance::Accurate).unwrap();
+        assert_eq!(results[0].0, "pk-7");
+        assert_eq!(results[1].0, "pk-42");
+    }
+
     #[test]
     fn test_vector_database_similarity_search() {
         let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
@@ -315,6 +1135,489 @@ mod tests {
         assert_eq!(results[0].0, "test1");
     }
 
+    #[test]
+    fn test_vector_database_search_excluding() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[1.0, 1.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[2.0, 2.0]), "test2".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[5.0, 5.0]), "test3".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[1.0, 1.0]);
+        let top = db.similarity_search(&query, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(top[0].0, "test1");
+
+        let exclude: HashSet<String> = ["test1".to_string()].into_iter().collect();
+        let results = db.search_excluding(&query, 2, &exclude, QueryPerformance::Accurate).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(key, _)| key != "test1"));
+        assert_eq!(results[0].0, "test2");
+    }
+
+    #[test]
+    fn test_vector_database_seeded_lsh_is_deterministic() {
+        let build = || {
+            let params = LSHParams { seed: Some(1234), ..LSHParams::default() };
+            let mut db = VectorDatabase::new(3, BackingStorage::LSHOnly, Some(params));
+            for i in 0..50 {
+                let value = i as f32;
+                db.insert(Vector::from_slice(&[value.sin(), value.cos(), (value * 0.3).sin()]), format!("key{i}")).unwrap();
+            }
+            db
+        };
+
+        let db1 = build();
+        let db2 = build();
+        for i in 0..10 {
+            let value = i as f32 * 0.7;
+            let query = Vector::from_slice(&[value.cos(), value.sin(), 0.2]);
+            assert_eq!(
+                db1.similarity_search(&query, 5, QueryPerformance::Fast).unwrap(),
+                db2.similarity_search(&query, 5, QueryPerformance::Fast).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_vector_database_normalize_all() {
+        let mut db = VectorDatabase::new(2, BackingStorage::Hybrid, None);
+        db.insert(Vector::from_slice(&[3.0, 4.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[-2.0, 0.0]), "test2".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[0.0, 0.0]), "zero".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[10.0, 10.0]), "test3".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[f32::NAN, 1.0]), "nan".to_string()).unwrap();
+        db.remove("test3");
+
+        let skipped = db.normalize_all().unwrap();
+        assert_eq!(skipped, 2);
+        assert_eq!(db.size(), 4);
+        assert_eq!(db.total_slots(), 4);
+
+        for (key, vector) in db.get_all_vectors() {
+            if key == "zero" {
+                assert_eq!(vector.norm(), 0.0);
+            } else if key == "nan" {
+                assert_eq!(vector[1], 1.0);
+            } else {
+                assert!((vector.norm() - 1.0).abs() < 1e-6);
+            }
+        }
+
+        let query = Vector::from_slice(&[0.6, 0.8]);
+        for performance in [QueryPerformance::Accurate, QueryPerformance::Fast] {
+            let results = db.similarity_search(&query, 1, performance).unwrap();
+            assert_eq!(results[0].0, "test1");
+            assert!(results[0].1 < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_vector_database_similarity_search_capped() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        for i in 0..200 {
+            db.insert(Vector::from_slice(&[((i * 37) % 199) as f32, ((i * 61) % 193) as f32]), format!("key{i}")).unwrap();
+        }
+        db.compact();
+
+        let query = Vector::from_slice(&[150.0, 20.0]);
+        let exact = db.similarity_search(&query, 5, QueryPerformance::Accurate).unwrap();
+        let high_cap = db.similarity_search_capped(&query, 5, Some(10_000), QueryPerformance::Accurate).unwrap();
+        assert_eq!(high_cap, exact);
+        assert_eq!(db.similarity_search_capped(&query, 5, None, QueryPerformance::Accurate).unwrap(), exact);
+
+        let low_cap = db.similarity_search_capped(&query, 5, Some(2), QueryPerformance::Accurate).unwrap();
+        assert!(low_cap.len() <= 2);
+        assert!(low_cap[0].1 > exact[0].1);
+    }
+
+    #[test]
+    fn test_vector_database_search_diverse() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        let centers = [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)];
+        for (cluster, &(x, y)) in centers.iter().enumerate() {
+            for i in 0..4 {
+                let offset = i as f32 * 0.05;
+                db.insert(Vector::from_slice(&[x + offset, y - offset]), format!("c{cluster}_{i}")).unwrap();
+            }
+        }
+
+        let query = Vector::from_slice(&[1.0, 1.0]);
+        let plain = db.similarity_search(&query, 3, QueryPerformance::Accurate).unwrap();
+        assert!(plain.iter().all(|(key, _)| key.starts_with("c0_")));
+
+        let diverse = db.search_diverse(&query, 3, 1.0, QueryPerformance::Accurate).unwrap();
+        assert_eq!(diverse.len(), 3);
+        let mut clusters: Vec<&str> = diverse.iter().map(|(key, _)| &key[..2]).collect();
+        clusters.sort();
+        assert_eq!(clusters, ["c0", "c1", "c2"]);
+        assert_eq!(diverse[0], plain[0]);
+
+        let all = db.search_diverse(&query, 5, 1.0, QueryPerformance::Accurate).unwrap();
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn test_vector_database_search_grouped() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        for i in 0..10 {
+            let category = if i % 2 == 0 { "books" } else { "music" };
+            db.insert_with_metadata(Vector::from_slice(&[i as f32, 0.0]), format!("item{i}"), format!("{category}:{i}")).unwrap();
+        }
+
+        let query = Vector::from_slice(&[0.0, 0.0]);
+        let results = db.search_grouped(&query, 3, |metadata| metadata.split(':').next().unwrap_or("").to_string(), QueryPerformance::Accurate).unwrap();
+        assert_eq!(results.len(), 6);
+
+        for category in ["books", "music"] {
+            let group: Vec<&SearchResult> = results.iter().filter(|result| result.metadata.starts_with(category)).collect();
+            assert_eq!(group.len(), 3);
+            assert!(group.windows(2).all(|pair| pair[0].distance <= pair[1].distance));
+        }
+        let books: Vec<&str> = results.iter().filter(|result| result.metadata.starts_with("books")).map(|result| result.key.as_str()).collect();
+        assert_eq!(books, ["item0", "item2", "item4"]);
+    }
+
+    #[test]
+    fn test_vector_database_validation_levels() {
+        let nan = Vector::from_slice(&[1.0, f32::NAN]);
+
+        let mut off = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        assert_eq!(off.validation_level(), ValidationLevel::Off);
+        off.insert(nan.clone(), "nan".to_string()).unwrap();
+
+        let mut paranoid = VectorDatabase::with_validation(2, BackingStorage::Hybrid, None, ValidationLevel::Paranoid);
+        let err = paranoid.insert(nan.clone(), "nan".to_string()).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::NonFiniteComponent { index: 1 }));
+        assert_eq!(paranoid.size(), 0);
+
+        let err = paranoid.insert(Vector::from_slice(&[f32::MAX, f32::MAX]), "huge".to_string()).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::NumericalOverflow(_)));
+
+        paranoid.insert(Vector::from_slice(&[1.0, 2.0]), "a".to_string()).unwrap();
+        paranoid.remove("a");
+        paranoid.insert(Vector::from_slice(&[3.0, 4.0]), "b".to_string()).unwrap();
+        assert_eq!(paranoid.size(), 1);
+
+        let mut basic = VectorDatabase::with_validation(2, BackingStorage::KDTreeOnly, None, ValidationLevel::Basic);
+        let err = basic.insert(Vector::from_slice(&[f32::INFINITY, 0.0]), "inf".to_string()).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::NonFiniteComponent { index: 0 }));
+        basic.insert(Vector::from_slice(&[f32::MAX, f32::MAX]), "huge".to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_vector_database_search_skips_non_finite_vectors() {
+        for backing_storage in [BackingStorage::KDTreeOnly, BackingStorage::LSHOnly, BackingStorage::Hybrid] {
+            let mut db = VectorDatabase::new(2, backing_storage, None);
+            db.insert(Vector::from_slice(&[f32::NAN, 0.0]), "nan".to_string()).unwrap();
+            db.insert(Vector::from_slice(&[1.0, 1.0]), "a".to_string()).unwrap();
+            db.insert(Vector::from_slice(&[-3.0, 1.0]), "b".to_string()).unwrap();
+
+            let query = Vector::from_slice(&[1.0, 1.0]);
+            for performance in [QueryPerformance::Accurate, QueryPerformance::Fast] {
+                let results = db.similarity_search(&query, 3, performance).unwrap();
+                assert_eq!(results.first(), Some(&("a".to_string(), 0.0)), "{backing_storage:?}");
+                assert!(results.iter().all(|(key, _)| key != "nan"));
+            }
+
+            let err = db.similarity_search(&Vector::from_slice(&[0.0, f32::NAN]), 1, QueryPerformance::Accurate).unwrap_err();
+            assert!(matches!(err.root_cause(), VectorError::NonFiniteComponent { index: 1 }));
+        }
+    }
+
+    #[test]
+    fn test_vector_database_from_word_vectors() {
+        let fixture = "3 2\nking 0.5 1.0\nqueen 0.25 -1.5\n\napple 3 4\n";
+        let (db, words) = VectorDatabase::from_word_vectors(fixture.as_bytes(), BackingStorage::KDTreeOnly, None).unwrap();
+        assert_eq!(words, ["king", "queen", "apple"]);
+        assert_eq!(db.dimensions(), 2);
+        assert_eq!(db.get_vector("king").unwrap().data(), &[0.5, 1.0]);
+        assert_eq!(db.get_vector("queen").unwrap().data(), &[0.25, -1.5]);
+        assert_eq!(db.get_vector("apple").unwrap().data(), &[3.0, 4.0]);
+
+        let (headerless, _) = VectorDatabase::from_word_vectors("a 1 0\nb 0 1\n".as_bytes(), BackingStorage::KDTreeOnly, None).unwrap();
+        assert_eq!(headerless.size(), 2);
+
+        let Err(err) = VectorDatabase::from_word_vectors("a 1 0\nb 0 x\n".as_bytes(), BackingStorage::KDTreeOnly, None) else {
+            panic!("expected a parse error");
+        };
+        assert!(matches!(err.root_cause(), VectorError::ParseError { line: 2 }));
+
+        let Err(err) = VectorDatabase::from_word_vectors("a 1 0\nb 0 1 2\n".as_bytes(), BackingStorage::KDTreeOnly, None) else {
+            panic!("expected a parse error");
+        };
+        assert!(matches!(err.root_cause(), VectorError::ParseError { line: 2 }));
+    }
+
+    #[test]
+    fn test_vector_database_flat_round_trip() {
+        let mut db = VectorDatabase::new(3, BackingStorage::Hybrid, None);
+        db.insert(Vector::from_slice(&[4.0, 5.0, 6.0]), "b".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[1.0, 2.0, 3.0]), "a".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[7.0, 8.0, 9.0]), "c".to_string()).unwrap();
+
+        let (keys, data, dimensions) = db.to_flat();
+        assert_eq!(keys, ["a", "b", "c"]);
+        assert_eq!(data, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(dimensions, 3);
+
+        let restored = VectorDatabase::from_flat(keys, data, dimensions, BackingStorage::Hybrid, None).unwrap();
+        assert_eq!(restored.size(), 3);
+        assert_eq!(restored.content_hash(), db.content_hash());
+        assert_eq!(restored.get_vector("b").unwrap().data(), &[4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_vector_database_from_flat_invalid_length() {
+        let keys = vec!["a".to_string()];
+        let Err(err) = VectorDatabase::from_flat(keys.clone(), vec![1.0; 4], 3, BackingStorage::KDTreeOnly, None) else {
+            panic!("expected an invalid flat length error");
+        };
+        assert!(matches!(err.root_cause(), VectorError::InvalidFlatLength { length: 4, dimensions: 3 }));
+
+        let Err(err) = VectorDatabase::from_flat(keys, vec![1.0; 6], 3, BackingStorage::KDTreeOnly, None) else {
+            panic!("expected a keys and vectors mismatch error");
+        };
+        assert!(matches!(err.root_cause(), VectorError::KeysAndVectorsMismatch));
+
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let Err(err) = VectorDatabase::from_flat(keys, Vec::new(), 0, BackingStorage::KDTreeOnly, None) else {
+            panic!("expected an invalid flat length error for zero dimensions");
+        };
+        assert!(matches!(err.root_cause(), VectorError::InvalidFlatLength { length: 0, dimensions: 0 }));
+    }
+
+    #[test]
+    fn test_vector_database_rbf_search() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[0.0, 0.0]), "origin".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[1.0, 0.0]), "near".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[3.0, 0.0]), "far".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[0.0, 0.0]);
+        let results = db.rbf_search(&query, 3, 0.5, QueryPerformance::Accurate).unwrap();
+        let keys: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["origin", "near", "far"]);
+        assert_eq!(results[0].1, 1.0);
+        assert!(results.windows(2).all(|pair| pair[0].1 > pair[1].1));
+
+        let err = db.rbf_search(&query, 3, -1.0, QueryPerformance::Accurate).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::InvalidParameter(_)));
+    }
+
+    #[test]
+    fn test_vector_database_search_stable() {
+        let mut db = VectorDatabase::new(2, BackingStorage::LSHOnly, None);
+        let points = [
+            ("delta", [1.0, 0.0]),
+            ("bravo", [0.0, 1.0]),
+            ("echo", [2.0, 0.0]),
+            ("alpha", [-1.0, 0.0]),
+            ("charlie", [0.0, -1.0]),
+            ("foxtrot", [0.6, 0.8]),
+        ];
+        for (key, point) in points {
+            db.insert(Vector::from_slice(&point), key.to_string()).unwrap();
+        }
+
+        let query = Vector::from_slice(&[0.0, 0.0]);
+        let results = db.search_stable(&query, 6).unwrap();
+        let keys: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["alpha", "bravo", "charlie", "delta", "foxtrot", "echo"]);
+        assert_eq!(results[0].1, 1.0);
+        assert_eq!(results[5].1, 2.0);
+
+        assert_eq!(db.search_stable(&query, 2).unwrap().len(), 2);
+        assert!(db.search_stable(&Vector::from_slice(&[1.0]), 1).is_err());
+    }
+
+    #[test]
+    fn test_vector_database_search_with_histogram() {
+        let mut db = VectorDatabase::new(1, BackingStorage::KDTreeOnly, None);
+        for (i, value) in [0.0, 1.0, 2.0, 3.0, 4.0, 9.0, 10.0].into_iter().enumerate() {
+            db.insert(Vector::from_slice(&[value]), format!("key{i}")).unwrap();
+        }
+
+        let query = Vector::from_slice(&[0.0]);
+        let (results, histogram) = db.search_with_histogram(&query, 2, 5, QueryPerformance::Accurate).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "key0");
+        assert_eq!(histogram.counts.iter().sum::<u32>(), 7);
+        assert_eq!(histogram.counts, [2, 2, 1, 0, 2]);
+        assert_eq!((histogram.min, histogram.max), (0.0, 10.0));
+
+        let (_, single) = db.search_with_histogram(&query, 2, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(single.counts, [7]);
+
+        let err = db.search_with_histogram(&query, 2, 0, QueryPerformance::Accurate).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::InvalidParameter(_)));
+    }
+
+    #[test]
+    fn test_vector_database_diff_and_apply() {
+        let mut base = VectorDatabase::new(2, BackingStorage::Hybrid, None);
+        for i in 0..6 {
+            base.insert_with_metadata(Vector::from_slice(&[i as f32, 1.0]), format!("key{i}"), format!("meta{i}")).unwrap();
+        }
+
+        let mut mutated = base.clone();
+        mutated.remove("key1");
+        mutated.remove("key4");
+        mutated.insert(Vector::from_slice(&[4.0, -4.0]), "key4".to_string()).unwrap();
+        mutated.insert(Vector::from_slice(&[9.0, 9.0]), "key9".to_string()).unwrap();
+        assert!(mutated.diff(&mutated).unwrap().is_empty());
+
+        let diff = mutated.diff(&base).unwrap();
+        assert_eq!(diff.added, [("key9".to_string(), Vector::from_slice(&[9.0, 9.0]))]);
+        assert_eq!(diff.updated, [("key4".to_string(), Vector::from_slice(&[4.0, -4.0]))]);
+        assert_eq!(diff.removed, ["key1"]);
+
+        let mut replica = base.clone();
+        replica.apply_diff(&diff).unwrap();
+        assert_eq!(replica.get_all_vectors(), mutated.get_all_vectors());
+        assert_eq!(replica.content_hash(), mutated.content_hash());
+        assert!(replica.diff(&mutated).unwrap().is_empty());
+        assert_eq!(replica.total_slots(), replica.size());
+        assert_eq!(replica.get_metadata("key4"), Some(&"meta4".to_string()));
+        assert_eq!(replica.get_metadata("key1"), None);
+
+        let query = Vector::from_slice(&[4.0, -3.0]);
+        assert_eq!(
+            replica.similarity_search(&query, 3, QueryPerformance::Accurate).unwrap(),
+            mutated.similarity_search(&query, 3, QueryPerformance::Accurate).unwrap()
+        );
+
+        let other = VectorDatabase::new(3, BackingStorage::KDTreeOnly, None);
+        assert!(matches!(other.diff(&base).unwrap_err().root_cause(), VectorError::DimensionsMismatch { .. }));
+    }
+
+    #[test]
+    fn test_vector_database_pooled_neighbor() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[1.0, 0.0]), "nearest".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[0.0, 3.0]), "middle".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[-4.0, 0.0]), "farthest".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[0.5, 0.0]);
+        let sharp = db.pooled_neighbor(&query, 3, 1e-3, QueryPerformance::Accurate).unwrap();
+        assert!((sharp[0] - 1.0).abs() < 1e-4 && sharp[1].abs() < 1e-4);
+
+        let smooth = db.pooled_neighbor(&query, 3, 1e6, QueryPerformance::Accurate).unwrap();
+        assert!((smooth[0] + 1.0).abs() < 1e-3 && (smooth[1] - 1.0).abs() < 1e-3);
+
+        let err = db.pooled_neighbor(&query, 3, 0.0, QueryPerformance::Accurate).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::InvalidParameter(_)));
+        let err = db.pooled_neighbor(&query, 0, 1.0, QueryPerformance::Accurate).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::EmptyInput));
+    }
+
+    #[test]
+    fn test_vector_database_search_range() {
+        let mut db = VectorDatabase::new(1, BackingStorage::KDTreeOnly, None);
+        for i in 0..10 {
+            db.insert(Vector::from_slice(&[i as f32 * 0.5]), format!("near{i}")).unwrap();
+        }
+        for i in 0..4 {
+            db.insert(Vector::from_slice(&[2.0]), format!("dup{i}")).unwrap();
+        }
+        db.insert(Vector::from_slice(&[100.0]), "far".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[0.0]);
+        let all = db.search_range(&query, 4.5, None, QueryPerformance::Accurate).unwrap();
+        assert_eq!(all.len(), 14);
+        assert!(all.iter().all(|(_, distance)| *distance <= 4.5));
+
+        let capped = db.search_range(&query, 4.5, Some(3), QueryPerformance::Accurate).unwrap();
+        let keys: Vec<&str> = capped.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["near0", "near1", "near2"]);
+
+        let ties = db.search_range(&Vector::from_slice(&[2.0]), 0.0, Some(3), QueryPerformance::Accurate).unwrap();
+        let keys: Vec<&str> = ties.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["dup0", "dup1", "dup2"]);
+    }
+
+    #[test]
+    fn test_vector_database_search_into() {
+        for backing_storage in [BackingStorage::KDTreeOnly, BackingStorage::LSHOnly] {
+            let params = LSHParams { seed: Some(3), ..LSHParams::default() };
+            let mut db = VectorDatabase::new(2, backing_storage, Some(params));
+            for i in 0..20 {
+                db.insert(Vector::from_slice(&[i as f32, (i % 3) as f32]), format!("key{i}")).unwrap();
+            }
+
+            let mut out = Vec::with_capacity(4);
+            let buffer = out.as_ptr();
+            for query in [Vector::from_slice(&[2.0, 1.0]), Vector::from_slice(&[15.0, 0.0])] {
+                db.search_into(&query, 4, &mut out, QueryPerformance::Accurate).unwrap();
+                assert_eq!(out, db.similarity_search(&query, 4, QueryPerformance::Accurate).unwrap());
+                assert_eq!(out.capacity(), 4);
+                assert_eq!(out.as_ptr(), buffer);
+            }
+
+            assert!(db.search_into(&Vector::from_slice(&[1.0]), 4, &mut out, QueryPerformance::Accurate).is_err());
+            assert!(out.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_vector_database_ties_at_k_broken_by_key() {
+        for backing_storage in [BackingStorage::KDTreeOnly, BackingStorage::LSHOnly, BackingStorage::Hybrid] {
+            let mut db = VectorDatabase::new(2, backing_storage, None);
+            for key in ["e", "d", "c", "b", "a"] {
+                db.insert(Vector::from_slice(&[1.0, 1.0]), key.to_string()).unwrap();
+            }
+            db.insert(Vector::from_slice(&[5.0, 5.0]), "far".to_string()).unwrap();
+
+            // Querying at the tied vector puts it in their LSH bucket whatever the random hash functions
+            let query = Vector::from_slice(&[1.0, 1.0]);
+            for performance in [QueryPerformance::Accurate, QueryPerformance::Fast] {
+                let results = db.similarity_search(&query, 2, performance).unwrap();
+                let keys: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+                assert_eq!(keys, ["a", "b"], "{backing_storage:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_vector_database_search_slice() {
+        for backing_storage in [BackingStorage::KDTreeOnly, BackingStorage::LSHOnly] {
+            let params = LSHParams { seed: Some(5), ..LSHParams::default() };
+            let mut db = VectorDatabase::new(3, backing_storage, Some(params));
+            for i in 0..30 {
+                let value = i as f32;
+                db.insert(Vector::from_slice(&[value.sin(), value.cos(), value * 0.1]), format!("key{i}")).unwrap();
+            }
+
+            let query = [0.3, -0.4, 1.2];
+            let expected = db.similarity_search(&Vector::from_slice(&query), 5, QueryPerformance::Accurate).unwrap();
+            assert_eq!(db.search_slice(&query, 5, QueryPerformance::Accurate).unwrap(), expected);
+
+            let err = db.search_slice(&query[..2], 5, QueryPerformance::Accurate).unwrap_err();
+            assert!(matches!(err.root_cause(), VectorError::DimensionsMismatch { expected: 3, found: 2 }));
+        }
+    }
+
+    #[test]
+    fn test_vector_database_search_by_id() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        for i in 0..6 {
+            db.insert(Vector::from_slice(&[i as f32, (i * i) as f32]), format!("key{i}")).unwrap();
+        }
+
+        let results = db.search_by_id("key2", 3, QueryPerformance::Accurate).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|(key, _)| key != "key2"));
+
+        let query = db.get_vector("key2").unwrap().clone();
+        let expected: Vec<(String, f32)> = db.similarity_search(&query, 4, QueryPerformance::Accurate)
+            .unwrap()
+            .into_iter()
+            .filter(|(key, _)| key != "key2")
+            .collect();
+        assert_eq!(results, expected);
+
+        let err = db.search_by_id("missing", 3, QueryPerformance::Accurate).unwrap_err();
+        assert!(matches!(err.root_cause(), VectorError::KeyNotFound));
+    }
+
     #[test]
     fn test_vector_database_batch_operations() {
         let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
@@ -334,4 +1637,233 @@ mod tests {
         let result = db.insert(vector, "test".to_string());
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_vector_database_errors_carry_operation_context() {
+        let mut db = VectorDatabase::new(3, BackingStorage::KDTreeOnly, None);
+        let err = db.insert(Vector::from_slice(&[1.0, 2.0]), "test".to_string()).unwrap_err();
+        assert!(matches!(err, VectorError::Context { op: "insert", .. }));
+        assert!(matches!(err.root_cause(), VectorError::DimensionsMismatch { expected: 3, found: 2 }));
+        assert_eq!(err.to_string(), "insert: Dimension mismatch: expected 3, found 2");
+
+        let err = db.similarity_search(&Vector::from_slice(&[1.0]), 1, QueryPerformance::Accurate).unwrap_err();
+        assert!(matches!(err, VectorError::Context { op: "similarity_search", .. }));
+    }
+
+    #[test]
+    fn test_vector_database_live_count_and_total_slots() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        for i in 0..10 {
+            db.insert(Vector::from_slice(&[i as f32, i as f32]), format!("key{i}")).unwrap();
+        }
+
+        db.remove("key0");
+        db.remove("key1");
+        db.remove("key2");
+
+        assert_eq!(db.live_count(), 7);
+        assert_eq!(db.total_slots(), 10);
+        assert_eq!(db.size(), db.live_count());
+    }
+
+    #[test]
+    fn test_vector_database_search_multi() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[1.0, 2.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[3.0, 4.0]), "test2".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[5.0, 6.0]), "test3".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[3.1, 3.9]);
+        let single = db.search_multi(std::slice::from_ref(&query), 2, QueryPerformance::Accurate).unwrap();
+        let direct = db.similarity_search(&query, 2, QueryPerformance::Accurate).unwrap();
+        assert_eq!(single, direct);
+
+        let queries = [Vector::from_slice(&[1.0, 2.0]), Vector::from_slice(&[5.0, 6.0])];
+        let results = db.search_multi(&queries, 1, QueryPerformance::Accurate).unwrap();
+        assert_eq!(results[0].0, "test2");
+
+        let mismatched = [Vector::from_slice(&[1.0, 2.0]), Vector::from_slice(&[1.0])];
+        assert!(db.search_multi(&mismatched, 1, QueryPerformance::Accurate).is_err());
+        assert!(db.search_multi(&[], 1, QueryPerformance::Accurate).is_err());
+    }
+
+    #[test]
+    fn test_vector_database_content_hash() {
+        let mut db = VectorDatabase::new(2, BackingStorage::Hybrid, None);
+        let empty_hash = db.content_hash();
+
+        db.insert(Vector::from_slice(&[1.0, 2.0]), "test1".to_string()).unwrap();
+        let one_hash = db.content_hash();
+        assert_ne!(one_hash, empty_hash);
+
+        db.insert(Vector::from_slice(&[3.0, 4.0]), "test2".to_string()).unwrap();
+        assert_ne!(db.content_hash(), one_hash);
+
+        db.remove("test2");
+        assert_eq!(db.content_hash(), one_hash);
+
+        let mut other = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        other.insert(Vector::from_slice(&[1.0, 2.0]), "test1".to_string()).unwrap();
+        assert_eq!(other.content_hash(), one_hash);
+    }
+
+    #[test]
+    fn test_vector_database_insert_normalized() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert_normalized(Vector::from_slice(&[3.0, 4.0]), "test".to_string()).unwrap();
+
+        let stored = db.get_vector("test").unwrap();
+        assert!((stored.norm() - 1.0).abs() < 1e-6);
+        assert!((stored[0] - 0.6).abs() < 1e-6);
+
+        let result = db.insert_normalized(Vector::new(2), "zero".to_string());
+        assert!(matches!(result.unwrap_err().root_cause(), VectorError::ZeroVector));
+        assert!(db.get_vector("zero").is_none());
+    }
+
+    #[test]
+    fn test_vector_database_memory_usage_after_compaction() {
+        let mut db = VectorDatabase::new(4, BackingStorage::Hybrid, None);
+        for i in 0..100 {
+            let value = i as f32;
+            db.insert_with_metadata(
+                Vector::from_slice(&[value, -value, value * 0.5, 1.0]),
+                format!("key{i}"),
+                format!("metadata{i}"),
+            ).unwrap();
+        }
+        for i in 0..40 {
+            db.remove(&format!("key{i}"));
+        }
+        let before = db.memory_usage();
+
+        db.compact();
+        db.shrink_to_fit();
+        assert_eq!(db.total_slots(), 60);
+        assert!(db.memory_usage() < before);
+    }
+
+    #[test]
+    fn test_vector_database_search_scored() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[1.0, 1.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[2.0, 3.0]), "test2".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[10.0, -4.0]), "test3".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[1.0, 1.0]);
+        let results = db.search_scored(&query, 3, QueryPerformance::Accurate).unwrap();
+        assert_eq!(results[0], ("test1".to_string(), 1.0));
+        assert!(results.iter().all(|(_, score)| (0.0..=1.0).contains(score)));
+        for i in 1..results.len() {
+            assert!(results[i - 1].1 >= results[i].1);
+        }
+    }
+
+    #[test]
+    fn test_vector_database_analyze() {
+        let mut db = VectorDatabase::new(3, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[0.6, 0.8, 0.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[1.0, 0.0, 0.0]), "test2".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[0.0, -1.0, 0.0]), "test3".to_string()).unwrap();
+
+        let analysis = db.analyze();
+        assert!(analysis.appears_normalized);
+        assert!((analysis.average_norm - 1.0).abs() < 1e-6);
+        assert!((analysis.near_zero_fraction - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(analysis.dimension_variances.len(), 3);
+        assert_eq!(analysis.dimension_variances[2], 0.0);
+        // Dimension 0 holds 0.6, 1.0, 0.0 with mean 8/15
+        assert!((analysis.dimension_variances[0] - 0.1689).abs() < 1e-3);
+
+        db.insert(Vector::from_slice(&[3.0, 4.0, 0.0]), "test4".to_string()).unwrap();
+        let analysis = db.analyze();
+        assert!(!analysis.appears_normalized);
+        assert!((analysis.average_norm - 2.0).abs() < 1e-6);
+
+        let empty = VectorDatabase::new(3, BackingStorage::KDTreeOnly, None).analyze();
+        assert!(!empty.appears_normalized);
+        assert_eq!(empty.average_norm, 0.0);
+    }
+
+    #[test]
+    fn test_vector_database_rerank() {
+        let mut db = VectorDatabase::new(1, BackingStorage::LSHOnly, None);
+        for i in 0..5 {
+            db.insert(Vector::from_slice(&[i as f32]), format!("key{i}")).unwrap();
+        }
+
+        let candidates: Vec<String> = ["key3", "key0", "key4", "key1"].iter().map(|key| key.to_string()).collect();
+        let query = Vector::from_slice(&[0.9]);
+        let results = db.rerank(&query, &candidates, 3).unwrap();
+        let keys: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["key1", "key0", "key3"]);
+        assert!((results[0].1 - 0.1).abs() < 1e-6);
+
+        let missing = vec!["missing".to_string()];
+        assert!(matches!(db.rerank(&query, &missing, 1).unwrap_err().root_cause(), VectorError::KeyNotFound));
+
+        db.insert(Vector::from_slice(&[f32::NAN]), "nan".to_string()).unwrap();
+        let with_nan = vec!["nan".to_string(), "key1".to_string()];
+        assert_eq!(db.rerank(&query, &with_nan, 2).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_vector_database_search_rounded() {
+        let mut db = VectorDatabase::new(1, BackingStorage::KDTreeOnly, None);
+        db.insert(Vector::from_slice(&[0.12344]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[0.12346]), "test2".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[0.5]), "test3".to_string()).unwrap();
+
+        let query = Vector::from_slice(&[0.0]);
+        let exact = db.similarity_search(&query, 3, QueryPerformance::Accurate).unwrap();
+        let rounded = db.search_rounded(&query, 3, 2, QueryPerformance::Accurate).unwrap();
+
+        let exact_keys: Vec<&String> = exact.iter().map(|(key, _)| key).collect();
+        let rounded_keys: Vec<&String> = rounded.iter().map(|(key, _)| key).collect();
+        assert_eq!(exact_keys, rounded_keys);
+        assert_eq!(rounded[0].1, 0.12);
+        assert_eq!(rounded[1].1, 0.12);
+        assert_eq!(rounded[2].1, 0.5);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_vector_database_emits_search_events() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata};
+
+        struct EventCounter(Arc<AtomicUsize>);
+
+        impl tracing::Subscriber for EventCounter {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                if event.metadata().target().starts_with("mini_vector_store_rs") {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let mut db = VectorDatabase::new(2, BackingStorage::Hybrid, None);
+        db.insert(Vector::from_slice(&[1.0, 2.0]), "test1".to_string()).unwrap();
+        db.insert(Vector::from_slice(&[3.0, 4.0]), "test2".to_string()).unwrap();
+
+        let count = Arc::new(AtomicUsize::new(0));
+        tracing::subscriber::with_default(EventCounter(count.clone()), || {
+            let query = Vector::from_slice(&[1.0, 1.0]);
+            db.similarity_search(&query, 1, QueryPerformance::Accurate).unwrap();
+            db.similarity_search(&query, 1, QueryPerformance::Fast).unwrap();
+        });
+        assert!(count.load(Ordering::SeqCst) >= 2);
+    }
 }
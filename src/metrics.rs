@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Snapshot of the operation counters of a `VectorDatabase`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StoreStats {
+    /// Searches dispatched to the KD-tree or LSH index
+    pub total_searches: u64,
+    /// Vectors inserted into the database, the rewrites done by `normalize_all` are not counted
+    pub total_inserts: u64,
+    /// Mean KD-tree nodes visited or LSH candidates scanned per search, 0.0 before any search
+    pub average_candidates_scanned: f64,
+}
+
+/// Atomic counters updated through shared references, so they work behind a shared database
+#[derive(Debug, Default)]
+pub(crate) struct StoreCounters {
+    searches: AtomicU64,
+    inserts: AtomicU64,
+    candidates_scanned: AtomicU64,
+}
+
+impl Clone for StoreCounters {
+    fn clone(&self) -> Self {
+        let counters = Self::default();
+        counters.searches.store(self.searches.load(Ordering::Relaxed), Ordering::Relaxed);
+        counters.inserts.store(self.inserts.load(Ordering::Relaxed), Ordering::Relaxed);
+        counters.candidates_scanned.store(self.candidates_scanned.load(Ordering::Relaxed), Ordering::Relaxed);
+        counters
+    }
+}
+
+impl StoreCounters {
+    /// Count one search that scanned `candidates_scanned` candidates
+    pub(crate) fn record_search(&self, candidates_scanned: usize) {
+        self.searches.fetch_add(1, Ordering::Relaxed);
+        self.candidates_scanned.fetch_add(candidates_scanned as u64, Ordering::Relaxed);
+    }
+
+    /// Count one inserted vector
+    pub(crate) fn record_insert(&self) {
+        self.inserts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Read the current counter values
+    pub(crate) fn snapshot(&self) -> StoreStats {
+        let total_searches = self.searches.load(Ordering::Relaxed);
+        let candidates_scanned = self.candidates_scanned.load(Ordering::Relaxed);
+        StoreStats {
+            total_searches,
+            total_inserts: self.inserts.load(Ordering::Relaxed),
+            average_candidates_scanned: if total_searches == 0 { 0.0 } else { candidates_scanned as f64 / total_searches as f64 },
+        }
+    }
+
+    /// Set every counter back to zero
+    pub(crate) fn reset(&self) {
+        self.searches.store(0, Ordering::Relaxed);
+        self.inserts.store(0, Ordering::Relaxed);
+        self.candidates_scanned.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BackingStorage, QueryPerformance, Vector, VectorDatabase};
+
+    #[test]
+    fn test_metrics_counters_track_operations() {
+        let mut db = VectorDatabase::new(2, BackingStorage::KDTreeOnly, None);
+        for i in 0..5 {
+            db.insert(Vector::from_slice(&[i as f32, 0.0]), format!("key{i}")).unwrap();
+        }
+        assert!(db.insert(Vector::from_slice(&[1.0]), "bad".to_string()).is_err());
+
+        let query = Vector::from_slice(&[2.0, 0.0]);
+        for _ in 0..3 {
+            db.similarity_search(&query, 2, QueryPerformance::Accurate).unwrap();
+        }
+        db.batch_similarity_search(vec![query.clone(), query.clone()], 1, QueryPerformance::Accurate).unwrap();
+
+        db.normalize_all().unwrap();
+        let stats = db.stats();
+        assert_eq!(stats.total_inserts, 5);
+        assert_eq!(stats.total_searches, 5);
+        assert!(stats.average_candidates_scanned >= 1.0 && stats.average_candidates_scanned <= 5.0);
+
+        db.reset_stats();
+        let stats = db.stats();
+        assert_eq!(stats.total_searches, 0);
+        assert_eq!(stats.total_inserts, 0);
+        assert_eq!(stats.average_candidates_scanned, 0.0);
+    }
+}